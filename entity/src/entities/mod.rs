@@ -2,10 +2,30 @@
 
 pub mod prelude;
 
+pub mod attendance;
+pub mod attendance_log;
+pub mod attendance_log_entry;
+pub mod attendance_nudge;
+pub mod attendance_requirement;
+pub mod bnb_fam;
+pub mod bnb_fam_member;
+pub mod bnb_meetup;
+pub mod bnb_meetup_fam;
+pub mod bnb_meetup_weight;
+pub mod bnb_signup;
+pub mod checkin_window;
+pub mod command_permission;
 pub mod matchy_meetup_opt_in;
 pub mod matchy_meetup_pair;
 pub mod matchy_meetup_pair_member;
 pub mod matchy_meetup_round;
+pub mod role_sync_change;
+pub mod roster_link;
+pub mod roster_link_request;
+pub mod roster_member;
+pub mod roster_snapshot;
+pub mod roster_snapshot_row;
+pub mod sea_orm_active_enums;
 pub mod server_calendar;
 pub mod server_event;
 pub mod snipe_opt_out;
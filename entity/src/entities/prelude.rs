@@ -1,11 +1,31 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.19
 
+pub use super::attendance::Entity as Attendance;
+pub use super::attendance_log::Entity as AttendanceLog;
+pub use super::attendance_log_entry::Entity as AttendanceLogEntry;
+pub use super::attendance_nudge::Entity as AttendanceNudge;
+pub use super::attendance_requirement::Entity as AttendanceRequirement;
+pub use super::bnb_fam::Entity as BnbFam;
+pub use super::bnb_fam_member::Entity as BnbFamMember;
+pub use super::bnb_meetup::Entity as BnbMeetup;
+pub use super::bnb_meetup_fam::Entity as BnbMeetupFam;
+pub use super::bnb_meetup_weight::Entity as BnbMeetupWeight;
+pub use super::bnb_signup::Entity as BnbSignup;
+pub use super::checkin_window::Entity as CheckinWindow;
+pub use super::command_permission::Entity as CommandPermission;
 pub use super::matchy_meetup_opt_in::Entity as MatchyMeetupOptIn;
 pub use super::matchy_meetup_pair::Entity as MatchyMeetupPair;
 pub use super::matchy_meetup_pair_member::Entity as MatchyMeetupPairMember;
 pub use super::matchy_meetup_round::Entity as MatchyMeetupRound;
+pub use super::role_sync_change::Entity as RoleSyncChange;
+pub use super::roster_link::Entity as RosterLink;
+pub use super::roster_link_request::Entity as RosterLinkRequest;
+pub use super::roster_member::Entity as RosterMember;
+pub use super::roster_snapshot::Entity as RosterSnapshot;
+pub use super::roster_snapshot_row::Entity as RosterSnapshotRow;
 pub use super::server_calendar::Entity as ServerCalendar;
 pub use super::server_event::Entity as ServerEvent;
 pub use super::snipe_opt_out::Entity as SnipeOptOut;
 pub use super::spotting_message::Entity as SpottingMessage;
 pub use super::spotting_victim::Entity as SpottingVictim;
+pub use super::user_stat::Entity as UserStat;
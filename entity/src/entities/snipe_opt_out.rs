@@ -7,6 +7,7 @@ use sea_orm::entity::prelude::*;
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: i64,
+    pub hide_history: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -11,6 +11,23 @@ mod m20251003_231509_calendar_indexes;
 mod m20251013_031245_message_on_delete_cascade;
 mod m20260112_055632_modernize_table_names;
 mod m20260223_234418_change_social_multiplier_mview;
+mod m20261019_041512_incremental_user_stat;
+mod m20261019_183027_snipe_opt_out_hide_history;
+mod m20261019_201344_attendance;
+mod m20261019_224106_checkin_window;
+mod m20261020_003518_attendance_requirements;
+mod m20261020_021544_attendance_log;
+mod m20261020_052207_roster_link;
+mod m20261020_071203_bnb_fam;
+mod m20261020_093017_bnb_meetup;
+mod m20261020_113540_bnb_joint_meetup;
+mod m20261020_140212_bnb_signup;
+mod m20261020_162748_role_sync_change;
+mod m20261020_184406_roster_snapshot;
+mod m20261020_201855_roster_member;
+mod m20261020_214032_command_permission;
+mod m20261021_010215_checkin_window_message;
+mod m20261021_032540_attendance_sheet_source;
 
 pub struct Migrator;
 
@@ -29,6 +46,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20251013_031245_message_on_delete_cascade::Migration),
             Box::new(m20260112_055632_modernize_table_names::Migration),
             Box::new(m20260223_234418_change_social_multiplier_mview::Migration),
+            Box::new(m20261019_041512_incremental_user_stat::Migration),
+            Box::new(m20261019_183027_snipe_opt_out_hide_history::Migration),
+            Box::new(m20261019_201344_attendance::Migration),
+            Box::new(m20261019_224106_checkin_window::Migration),
+            Box::new(m20261020_003518_attendance_requirements::Migration),
+            Box::new(m20261020_021544_attendance_log::Migration),
+            Box::new(m20261020_052207_roster_link::Migration),
+            Box::new(m20261020_071203_bnb_fam::Migration),
+            Box::new(m20261020_093017_bnb_meetup::Migration),
+            Box::new(m20261020_113540_bnb_joint_meetup::Migration),
+            Box::new(m20261020_140212_bnb_signup::Migration),
+            Box::new(m20261020_162748_role_sync_change::Migration),
+            Box::new(m20261020_184406_roster_snapshot::Migration),
+            Box::new(m20261020_201855_roster_member::Migration),
+            Box::new(m20261020_214032_command_permission::Migration),
+            Box::new(m20261021_010215_checkin_window_message::Migration),
+            Box::new(m20261021_032540_attendance_sheet_source::Migration),
         ]
     }
 }
@@ -0,0 +1,256 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+
+        conn.execute_unprepared("DROP MATERIALIZED VIEW user_stat")
+            .await?;
+
+        // full recomputation, kept around so the stats table can be checked against it
+        conn.execute_unprepared(
+            r#"
+CREATE VIEW user_stat_expected AS SELECT u.id,
+       COALESCE(socials_initiated.cnt, 0)::bigint AS socials_initiated,
+       COALESCE(snipes_initiated.cnt, 0)::bigint  AS snipes_initiated,
+       COALESCE(socials_victim.cnt, 0)::bigint    AS socials_victim,
+       COALESCE(snipes_victim.cnt, 0)::bigint     AS snipes_victim
+FROM (SELECT DISTINCT author_id AS id
+      FROM spotting_message
+      UNION
+      SELECT DISTINCT victim_id
+      FROM spotting_victim) u
+         LEFT JOIN (SELECT author_id, COUNT(*) AS cnt
+                    FROM spotting_message msg
+                    WHERE msg.is_social
+                    GROUP BY author_id) socials_initiated ON u.id = socials_initiated.author_id
+         LEFT JOIN (SELECT author_id, COUNT(*) AS cnt
+                    FROM spotting_message msg
+                             LEFT JOIN spotting_victim v ON msg.message_id = v.message_id
+                    WHERE NOT msg.is_social
+                    GROUP BY author_id) snipes_initiated ON u.id = snipes_initiated.author_id
+         LEFT JOIN (SELECT victim_id, COUNT(*) AS cnt
+                    FROM spotting_victim v
+                             INNER JOIN spotting_message msg ON v.message_id = msg.message_id
+                    WHERE msg.is_social
+                    GROUP BY victim_id) socials_victim ON u.id = socials_victim.victim_id
+         LEFT JOIN (SELECT victim_id, COUNT(*) AS cnt
+                    FROM spotting_victim v
+                             INNER JOIN spotting_message msg ON v.message_id = msg.message_id
+                    WHERE NOT msg.is_social
+                    GROUP BY victim_id) snipes_victim ON u.id = snipes_victim.victim_id;
+            "#,
+        )
+        .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserStat::Table)
+                    .col(ColumnDef::new(UserStat::Id).big_integer().primary_key())
+                    .col(
+                        ColumnDef::new(UserStat::SocialsInitiated)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(UserStat::SnipesInitiated)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(UserStat::SocialsVictim)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(UserStat::SnipesVictim)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        conn.execute_unprepared("INSERT INTO user_stat SELECT * FROM user_stat_expected")
+            .await?;
+
+        // recomputes the stats of a single user; the advisory lock serializes concurrent
+        // refreshes of the same user so that the later one always sees the earlier one's rows
+        conn.execute_unprepared(
+            r#"
+CREATE FUNCTION refresh_user_stat(uid bigint) RETURNS void
+    LANGUAGE plpgsql AS
+$$
+BEGIN
+    PERFORM pg_advisory_xact_lock(uid);
+
+    INSERT INTO user_stat (id, socials_initiated, snipes_initiated, socials_victim, snipes_victim)
+    SELECT uid,
+           (SELECT COUNT(*)
+            FROM spotting_message msg
+            WHERE msg.author_id = uid
+              AND msg.is_social),
+           (SELECT COUNT(*)
+            FROM spotting_message msg
+                     LEFT JOIN spotting_victim v ON msg.message_id = v.message_id
+            WHERE msg.author_id = uid
+              AND NOT msg.is_social),
+           (SELECT COUNT(*)
+            FROM spotting_victim v
+                     INNER JOIN spotting_message msg ON v.message_id = msg.message_id
+            WHERE v.victim_id = uid
+              AND msg.is_social),
+           (SELECT COUNT(*)
+            FROM spotting_victim v
+                     INNER JOIN spotting_message msg ON v.message_id = msg.message_id
+            WHERE v.victim_id = uid
+              AND NOT msg.is_social)
+    ON CONFLICT (id) DO UPDATE SET socials_initiated = EXCLUDED.socials_initiated,
+                                   snipes_initiated  = EXCLUDED.snipes_initiated,
+                                   socials_victim    = EXCLUDED.socials_victim,
+                                   snipes_victim     = EXCLUDED.snipes_victim;
+
+    DELETE
+    FROM user_stat
+    WHERE id = uid
+      AND NOT EXISTS (SELECT 1 FROM spotting_message WHERE author_id = uid)
+      AND NOT EXISTS (SELECT 1 FROM spotting_victim WHERE victim_id = uid);
+END
+$$;
+            "#,
+        )
+        .await?;
+
+        conn.execute_unprepared(
+            r#"
+CREATE FUNCTION spotting_message_user_stat() RETURNS trigger
+    LANGUAGE plpgsql AS
+$$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM refresh_user_stat(OLD.author_id);
+        PERFORM refresh_user_stat(v.victim_id) FROM spotting_victim v WHERE v.message_id = OLD.message_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM refresh_user_stat(NEW.author_id);
+        PERFORM refresh_user_stat(v.victim_id) FROM spotting_victim v WHERE v.message_id = NEW.message_id;
+    END IF;
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER spotting_message_user_stat
+    AFTER INSERT OR DELETE OR UPDATE OF message_id, author_id, is_social
+    ON spotting_message
+    FOR EACH ROW
+EXECUTE FUNCTION spotting_message_user_stat();
+            "#,
+        )
+        .await?;
+
+        conn.execute_unprepared(
+            r#"
+CREATE FUNCTION spotting_victim_user_stat() RETURNS trigger
+    LANGUAGE plpgsql AS
+$$
+BEGIN
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        PERFORM refresh_user_stat(OLD.victim_id);
+        PERFORM refresh_user_stat(msg.author_id) FROM spotting_message msg WHERE msg.message_id = OLD.message_id;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        PERFORM refresh_user_stat(NEW.victim_id);
+        PERFORM refresh_user_stat(msg.author_id) FROM spotting_message msg WHERE msg.message_id = NEW.message_id;
+    END IF;
+    RETURN NULL;
+END
+$$;
+
+CREATE TRIGGER spotting_victim_user_stat
+    AFTER INSERT OR DELETE OR UPDATE OF message_id, victim_id
+    ON spotting_victim
+    FOR EACH ROW
+EXECUTE FUNCTION spotting_victim_user_stat();
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let conn = manager.get_connection();
+
+        conn.execute_unprepared(
+            r#"
+DROP TRIGGER spotting_victim_user_stat ON spotting_victim;
+DROP TRIGGER spotting_message_user_stat ON spotting_message;
+DROP FUNCTION spotting_victim_user_stat();
+DROP FUNCTION spotting_message_user_stat();
+DROP FUNCTION refresh_user_stat(bigint);
+            "#,
+        )
+        .await?;
+
+        manager
+            .drop_table(Table::drop().table(UserStat::Table).to_owned())
+            .await?;
+
+        conn.execute_unprepared(
+            r#"
+CREATE MATERIALIZED VIEW user_stat AS SELECT u.id,
+       COALESCE(socials_initiated.cnt, 0)::bigint AS socials_initiated,
+       COALESCE(snipes_initiated.cnt, 0)::bigint  AS snipes_initiated,
+       COALESCE(socials_victim.cnt, 0)::bigint    AS socials_victim,
+       COALESCE(snipes_victim.cnt, 0)::bigint     AS snipes_victim
+FROM (SELECT DISTINCT author_id AS id
+      FROM spotting_message
+      UNION
+      SELECT DISTINCT victim_id
+      FROM spotting_victim) u
+         LEFT JOIN (SELECT author_id, COUNT(*) AS cnt
+                    FROM spotting_message msg
+                    WHERE msg.is_social
+                    GROUP BY author_id) socials_initiated ON u.id = socials_initiated.author_id
+         LEFT JOIN (SELECT author_id, COUNT(*) AS cnt
+                    FROM spotting_message msg
+                             LEFT JOIN spotting_victim v ON msg.message_id = v.message_id
+                    WHERE NOT msg.is_social
+                    GROUP BY author_id) snipes_initiated ON u.id = snipes_initiated.author_id
+         LEFT JOIN (SELECT victim_id, COUNT(*) AS cnt
+                    FROM spotting_victim v
+                             INNER JOIN spotting_message msg ON v.message_id = msg.message_id
+                    WHERE msg.is_social
+                    GROUP BY victim_id) socials_victim ON u.id = socials_victim.victim_id
+         LEFT JOIN (SELECT victim_id, COUNT(*) AS cnt
+                    FROM spotting_victim v
+                             INNER JOIN spotting_message msg ON v.message_id = msg.message_id
+                    WHERE NOT msg.is_social
+                    GROUP BY victim_id) snipes_victim ON u.id = snipes_victim.victim_id;
+DROP VIEW user_stat_expected;
+            "#,
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserStat {
+    Table,
+    Id,
+    SocialsInitiated,
+    SnipesInitiated,
+    SocialsVictim,
+    SnipesVictim,
+}
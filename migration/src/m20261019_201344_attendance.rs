@@ -0,0 +1,101 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AttendanceSource::Enum)
+                    .values([
+                        AttendanceSource::Checkin,
+                        AttendanceSource::Log,
+                        AttendanceSource::Bulk,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attendance::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Attendance::Id))
+                    .col(text(Attendance::Email))
+                    .col(big_integer_null(Attendance::DiscordId))
+                    .col(text_null(Attendance::EventName))
+                    .col(timestamp(Attendance::AttendedAt).default(Expr::cust("NOW()")))
+                    .col(enumeration(
+                        Attendance::Source,
+                        AttendanceSource::Enum,
+                        [
+                            AttendanceSource::Checkin,
+                            AttendanceSource::Log,
+                            AttendanceSource::Bulk,
+                        ],
+                    ))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_attendance_email")
+                    .table(Attendance::Table)
+                    .col(Attendance::Email)
+                    .to_owned(),
+            )
+            .await?;
+
+        // lets the sheet import be rerun without duplicating rows
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_attendance_unique_entry")
+                    .table(Attendance::Table)
+                    .col(Attendance::Email)
+                    .col(Attendance::AttendedAt)
+                    .col(Attendance::EventName)
+                    .unique()
+                    .nulls_not_distinct()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Attendance::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(AttendanceSource::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Attendance {
+    Table,
+    Id,
+    Email,
+    DiscordId,
+    EventName,
+    AttendedAt,
+    Source,
+}
+
+#[derive(DeriveIden)]
+enum AttendanceSource {
+    #[sea_orm(iden = "attendance_source")]
+    Enum,
+    Checkin,
+    Log,
+    Bulk,
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CheckinWindow::Table)
+                    .if_not_exists()
+                    .col(pk_auto(CheckinWindow::Id))
+                    .col(big_integer(CheckinWindow::GuildId))
+                    .col(text(CheckinWindow::EventName))
+                    // the code itself, or the secret rotating codes are derived from
+                    .col(text(CheckinWindow::Code))
+                    .col(boolean(CheckinWindow::Rotating))
+                    .col(big_integer_null(CheckinWindow::GuildEventId))
+                    .col(big_integer(CheckinWindow::OpenedBy))
+                    .col(timestamp(CheckinWindow::OpensAt).default(Expr::cust("NOW()")))
+                    .col(timestamp(CheckinWindow::ClosesAt))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Attendance::Table)
+                    .add_column(integer_null(Attendance::CheckinWindowId))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_attendance_checkin_window")
+                            .from_tbl(Attendance::Table)
+                            .from_col(Attendance::CheckinWindowId)
+                            .to_tbl(CheckinWindow::Table)
+                            .to_col(CheckinWindow::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Attendance::Table)
+                    .drop_foreign_key(Alias::new("fk_attendance_checkin_window"))
+                    .drop_column(Attendance::CheckinWindowId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(CheckinWindow::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CheckinWindow {
+    Table,
+    Id,
+    GuildId,
+    EventName,
+    Code,
+    Rotating,
+    GuildEventId,
+    OpenedBy,
+    OpensAt,
+    ClosesAt,
+}
+
+#[derive(DeriveIden)]
+enum Attendance {
+    Table,
+    CheckinWindowId,
+}
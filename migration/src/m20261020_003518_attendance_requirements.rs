@@ -0,0 +1,62 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AttendanceRequirement::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AttendanceRequirement::Id))
+                    .col(text_uniq(AttendanceRequirement::Name))
+                    .col(text_null(AttendanceRequirement::EventPattern))
+                    .col(integer(AttendanceRequirement::MinCount))
+                    .col(text_null(AttendanceRequirement::Committee))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AttendanceNudge::Table)
+                    .if_not_exists()
+                    .col(text(AttendanceNudge::Email))
+                    .col(timestamp(AttendanceNudge::LastSentAt))
+                    .primary_key(Index::create().col(AttendanceNudge::Email))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AttendanceNudge::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AttendanceRequirement::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AttendanceRequirement {
+    Table,
+    Id,
+    Name,
+    EventPattern,
+    MinCount,
+    Committee,
+}
+
+#[derive(DeriveIden)]
+enum AttendanceNudge {
+    Table,
+    Email,
+    LastSentAt,
+}
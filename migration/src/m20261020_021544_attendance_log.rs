@@ -0,0 +1,119 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AttendanceLog::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AttendanceLog::Id))
+                    .col(big_integer(AttendanceLog::GuildId))
+                    .col(big_integer(AttendanceLog::LoggedBy))
+                    // the message attendance was logged from, if any
+                    .col(big_integer_null(AttendanceLog::ChannelId))
+                    .col(big_integer_null(AttendanceLog::MessageId))
+                    .col(text_null(AttendanceLog::EventName))
+                    .col(custom(AttendanceLog::Source, AttendanceSource::Enum))
+                    .col(timestamp(AttendanceLog::LoggedAt).default(Expr::cust("NOW()")))
+                    .col(timestamp_null(AttendanceLog::UndoneAt))
+                    .col(big_integer_null(AttendanceLog::UndoneBy))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AttendanceLogEntry::Table)
+                    .if_not_exists()
+                    .col(pk_auto(AttendanceLogEntry::Id))
+                    .col(integer(AttendanceLogEntry::LogId))
+                    .col(big_integer_null(AttendanceLogEntry::DiscordId))
+                    // null when the user wasn't on the roster
+                    .col(text_null(AttendanceLogEntry::Email))
+                    .col(text_null(AttendanceLogEntry::MemberName))
+                    .col(integer_null(AttendanceLogEntry::AttendanceId))
+                    .col(boolean(AttendanceLogEntry::Recorded))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_attendance_log_entry_log")
+                            .from(AttendanceLogEntry::Table, AttendanceLogEntry::LogId)
+                            .to(AttendanceLog::Table, AttendanceLog::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_attendance_log_entry_attendance")
+                            .from(AttendanceLogEntry::Table, AttendanceLogEntry::AttendanceId)
+                            .to(Attendance::Table, Attendance::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_attendance_log_entry_log")
+                    .table(AttendanceLogEntry::Table)
+                    .col(AttendanceLogEntry::LogId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AttendanceLogEntry::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(AttendanceLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AttendanceLog {
+    Table,
+    Id,
+    GuildId,
+    LoggedBy,
+    ChannelId,
+    MessageId,
+    EventName,
+    Source,
+    LoggedAt,
+    UndoneAt,
+    UndoneBy,
+}
+
+#[derive(DeriveIden)]
+enum AttendanceLogEntry {
+    Table,
+    Id,
+    LogId,
+    DiscordId,
+    Email,
+    MemberName,
+    AttendanceId,
+    Recorded,
+}
+
+#[derive(DeriveIden)]
+enum Attendance {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum AttendanceSource {
+    #[sea_orm(iden = "attendance_source")]
+    Enum,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RosterLink::Table)
+                    .if_not_exists()
+                    .col(big_integer(RosterLink::DiscordId).primary_key())
+                    // lowercased, so each roster member links at most one account
+                    .col(text_uniq(RosterLink::Email))
+                    .col(timestamp(RosterLink::LinkedAt).default(Expr::cust("NOW()")))
+                    // the board member who approved it, or null if verified by email code
+                    .col(big_integer_null(RosterLink::ApprovedBy))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RosterLinkRequest::Table)
+                    .if_not_exists()
+                    .col(big_integer(RosterLinkRequest::DiscordId).primary_key())
+                    .col(text(RosterLinkRequest::Email))
+                    .col(text_null(RosterLinkRequest::Code))
+                    .col(big_integer_null(RosterLinkRequest::ApprovalMessageId))
+                    .col(timestamp(RosterLinkRequest::RequestedAt).default(Expr::cust("NOW()")))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RosterLinkRequest::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(RosterLink::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RosterLink {
+    Table,
+    DiscordId,
+    Email,
+    LinkedAt,
+    ApprovedBy,
+}
+
+#[derive(DeriveIden)]
+enum RosterLinkRequest {
+    Table,
+    DiscordId,
+    Email,
+    Code,
+    ApprovalMessageId,
+    RequestedAt,
+}
@@ -0,0 +1,107 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(BnbFamRole::Enum)
+                    .values([BnbFamRole::Bit, BnbFamRole::Byte])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(BnbFam::Table)
+                    .if_not_exists()
+                    .col(pk_auto(BnbFam::Id))
+                    .col(text_uniq(BnbFam::Name))
+                    .col(timestamp(BnbFam::CreatedAt).default(Expr::cust("NOW()")))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(BnbFamMember::Table)
+                    .if_not_exists()
+                    .col(integer(BnbFamMember::FamId))
+                    .col(big_integer(BnbFamMember::DiscordId))
+                    .col(enumeration(
+                        BnbFamMember::Role,
+                        BnbFamRole::Enum,
+                        [BnbFamRole::Bit, BnbFamRole::Byte],
+                    ))
+                    .primary_key(
+                        Index::create()
+                            .col(BnbFamMember::FamId)
+                            .col(BnbFamMember::DiscordId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_bnb_fam_member_fam")
+                            .from(BnbFamMember::Table, BnbFamMember::FamId)
+                            .to(BnbFam::Table, BnbFam::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_bnb_fam_member_discord_id")
+                    .table(BnbFamMember::Table)
+                    .col(BnbFamMember::DiscordId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BnbFamMember::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(BnbFam::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(BnbFamRole::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BnbFam {
+    Table,
+    Id,
+    Name,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum BnbFamMember {
+    Table,
+    FamId,
+    DiscordId,
+    Role,
+}
+
+#[derive(DeriveIden)]
+enum BnbFamRole {
+    #[sea_orm(iden = "bnb_fam_role")]
+    Enum,
+    Bit,
+    Byte,
+}
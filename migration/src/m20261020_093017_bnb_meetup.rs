@@ -0,0 +1,120 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(BnbMeetupType::Enum)
+                    .values([
+                        BnbMeetupType::Hangout,
+                        BnbMeetupType::Joint,
+                        BnbMeetupType::Official,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(BnbMeetup::Table)
+                    .if_not_exists()
+                    .col(pk_auto(BnbMeetup::Id))
+                    .col(integer(BnbMeetup::FamId))
+                    .col(custom(BnbMeetup::MeetupType, BnbMeetupType::Enum))
+                    // the message the meetup was logged from
+                    .col(big_integer(BnbMeetup::GuildId))
+                    .col(big_integer(BnbMeetup::ChannelId))
+                    .col(big_integer(BnbMeetup::MessageId))
+                    .col(timestamp(BnbMeetup::MetAt))
+                    .col(big_integer(BnbMeetup::LoggedBy))
+                    .col(timestamp(BnbMeetup::LoggedAt).default(Expr::cust("NOW()")))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_bnb_meetup_fam")
+                            .from(BnbMeetup::Table, BnbMeetup::FamId)
+                            .to(BnbFam::Table, BnbFam::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_bnb_meetup_fam")
+                    .table(BnbMeetup::Table)
+                    .col(BnbMeetup::FamId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(BnbMeetupWeight::Table)
+                    .if_not_exists()
+                    .col(custom(BnbMeetupWeight::MeetupType, BnbMeetupType::Enum).primary_key())
+                    .col(integer(BnbMeetupWeight::Points))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BnbMeetupWeight::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(BnbMeetup::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(BnbMeetupType::Enum).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BnbMeetup {
+    Table,
+    Id,
+    FamId,
+    MeetupType,
+    GuildId,
+    ChannelId,
+    MessageId,
+    MetAt,
+    LoggedBy,
+    LoggedAt,
+}
+
+#[derive(DeriveIden)]
+enum BnbMeetupWeight {
+    Table,
+    MeetupType,
+    Points,
+}
+
+#[derive(DeriveIden)]
+enum BnbFam {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum BnbMeetupType {
+    #[sea_orm(iden = "bnb_meetup_type")]
+    Enum,
+    Hangout,
+    Joint,
+    Official,
+}
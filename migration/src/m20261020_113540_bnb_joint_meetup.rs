@@ -0,0 +1,185 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BnbMeetupFam::Table)
+                    .if_not_exists()
+                    .col(integer(BnbMeetupFam::MeetupId))
+                    .col(integer(BnbMeetupFam::FamId))
+                    .primary_key(
+                        Index::create()
+                            .col(BnbMeetupFam::MeetupId)
+                            .col(BnbMeetupFam::FamId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_bnb_meetup_fam_meetup")
+                            .from(BnbMeetupFam::Table, BnbMeetupFam::MeetupId)
+                            .to(BnbMeetup::Table, BnbMeetup::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_bnb_meetup_fam_fam")
+                            .from(BnbMeetupFam::Table, BnbMeetupFam::FamId)
+                            .to(BnbFam::Table, BnbFam::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // a message logged more than once (e.g. once per fam of a joint meetup) becomes one
+        // meetup crediting every fam it was logged for
+        let conn = manager.get_connection();
+        conn.execute_unprepared(
+            r"
+            INSERT INTO bnb_meetup_fam (meetup_id, fam_id)
+            SELECT (SELECT MIN(first.id) FROM bnb_meetup first WHERE first.message_id = meetup.message_id),
+                   meetup.fam_id
+            FROM bnb_meetup meetup
+            ON CONFLICT DO NOTHING
+            ",
+        )
+        .await?;
+        conn.execute_unprepared(
+            r"
+            DELETE FROM bnb_meetup meetup
+            USING bnb_meetup first
+            WHERE meetup.message_id = first.message_id AND meetup.id > first.id
+            ",
+        )
+        .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_bnb_meetup_fam")
+                    .table(BnbMeetup::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BnbMeetup::Table)
+                    .drop_column(BnbMeetup::FamId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_bnb_meetup_message_id")
+                    .table(BnbMeetup::Table)
+                    .col(BnbMeetup::MessageId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_bnb_meetup_fam_fam")
+                    .table(BnbMeetupFam::Table)
+                    .col(BnbMeetupFam::FamId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_bnb_meetup_message_id")
+                    .table(BnbMeetup::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BnbMeetup::Table)
+                    .add_column(integer_null(BnbMeetup::FamId))
+                    .to_owned(),
+            )
+            .await?;
+
+        // joint meetups keep only one of their fams
+        let conn = manager.get_connection();
+        conn.execute_unprepared(
+            r"
+            UPDATE bnb_meetup meetup
+            SET fam_id = (SELECT MIN(fam_id) FROM bnb_meetup_fam WHERE meetup_id = meetup.id)
+            ",
+        )
+        .await?;
+        conn.execute_unprepared("DELETE FROM bnb_meetup WHERE fam_id IS NULL")
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(BnbMeetupFam::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BnbMeetup::Table)
+                    .modify_column(integer(BnbMeetup::FamId))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_bnb_meetup_fam")
+                            .from_tbl(BnbMeetup::Table)
+                            .from_col(BnbMeetup::FamId)
+                            .to_tbl(BnbFam::Table)
+                            .to_col(BnbFam::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_bnb_meetup_fam")
+                    .table(BnbMeetup::Table)
+                    .col(BnbMeetup::FamId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BnbMeetup {
+    Table,
+    Id,
+    FamId,
+    MessageId,
+}
+
+#[derive(DeriveIden)]
+enum BnbMeetupFam {
+    Table,
+    MeetupId,
+    FamId,
+}
+
+#[derive(DeriveIden)]
+enum BnbFam {
+    Table,
+    Id,
+}
@@ -0,0 +1,74 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BnbSignup::Table)
+                    .if_not_exists()
+                    .col(big_integer(BnbSignup::DiscordId).primary_key())
+                    .col(custom(BnbSignup::Role, BnbFamRole::Enum))
+                    // comma separated, as entered in the signup modal
+                    .col(text(BnbSignup::Interests))
+                    .col(text(BnbSignup::Preferences))
+                    .col(timestamp(BnbSignup::UpdatedAt).default(Expr::cust("NOW()")))
+                    .to_owned(),
+            )
+            .await?;
+
+        // the role and private channel created for fams made by `/bnb manage assign`
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BnbFam::Table)
+                    .add_column(big_integer_null(BnbFam::RoleId))
+                    .add_column(big_integer_null(BnbFam::ChannelId))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(BnbFam::Table)
+                    .drop_column(BnbFam::RoleId)
+                    .drop_column(BnbFam::ChannelId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(BnbSignup::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BnbSignup {
+    Table,
+    DiscordId,
+    Role,
+    Interests,
+    Preferences,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum BnbFam {
+    Table,
+    RoleId,
+    ChannelId,
+}
+
+#[derive(DeriveIden)]
+enum BnbFamRole {
+    #[sea_orm(iden = "bnb_fam_role")]
+    Enum,
+}
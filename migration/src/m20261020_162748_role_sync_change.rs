@@ -0,0 +1,57 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoleSyncChange::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RoleSyncChange::Id))
+                    .col(big_integer(RoleSyncChange::GuildId))
+                    .col(big_integer(RoleSyncChange::DiscordId))
+                    .col(big_integer(RoleSyncChange::RoleId))
+                    .col(text(RoleSyncChange::RoleName))
+                    // false when the role was removed
+                    .col(boolean(RoleSyncChange::Added))
+                    // null for the nightly sync
+                    .col(big_integer_null(RoleSyncChange::AppliedBy))
+                    .col(timestamp(RoleSyncChange::AppliedAt).default(Expr::cust("NOW()")))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_role_sync_change_discord_id")
+                    .table(RoleSyncChange::Table)
+                    .col(RoleSyncChange::DiscordId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RoleSyncChange::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RoleSyncChange {
+    Table,
+    Id,
+    GuildId,
+    DiscordId,
+    RoleId,
+    RoleName,
+    Added,
+    AppliedBy,
+    AppliedAt,
+}
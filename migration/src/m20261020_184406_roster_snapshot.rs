@@ -0,0 +1,83 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RosterSnapshot::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RosterSnapshot::Id))
+                    .col(timestamp(RosterSnapshot::TakenAt).default(Expr::cust("NOW()")))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RosterSnapshotRow::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RosterSnapshotRow::Id))
+                    .col(integer(RosterSnapshotRow::SnapshotId))
+                    .col(text(RosterSnapshotRow::Name))
+                    .col(text(RosterSnapshotRow::Email))
+                    .col(text(RosterSnapshotRow::Discord))
+                    // comma separated, as parsed from the sheet
+                    .col(text(RosterSnapshotRow::Committees))
+                    .col(big_integer_null(RosterSnapshotRow::DiscordId))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_roster_snapshot_row_snapshot")
+                            .from(RosterSnapshotRow::Table, RosterSnapshotRow::SnapshotId)
+                            .to(RosterSnapshot::Table, RosterSnapshot::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_roster_snapshot_row_snapshot_id")
+                    .table(RosterSnapshotRow::Table)
+                    .col(RosterSnapshotRow::SnapshotId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RosterSnapshotRow::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(RosterSnapshot::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RosterSnapshot {
+    Table,
+    Id,
+    TakenAt,
+}
+
+#[derive(DeriveIden)]
+enum RosterSnapshotRow {
+    Table,
+    Id,
+    SnapshotId,
+    Name,
+    Email,
+    Discord,
+    Committees,
+    DiscordId,
+}
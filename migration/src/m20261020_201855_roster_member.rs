@@ -0,0 +1,42 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(RosterMember::Table)
+                    .if_not_exists()
+                    .col(pk_auto(RosterMember::Id))
+                    .col(text(RosterMember::Name))
+                    .col(text_uniq(RosterMember::Email))
+                    .col(text(RosterMember::Discord))
+                    // comma separated, like the roster sheet
+                    .col(text(RosterMember::Committees))
+                    .col(big_integer_null(RosterMember::DiscordId))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RosterMember::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RosterMember {
+    Table,
+    Id,
+    Name,
+    Email,
+    Discord,
+    Committees,
+    DiscordId,
+}
@@ -0,0 +1,44 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CommandPermission::Table)
+                    .if_not_exists()
+                    // qualified name, e.g. "shortlink create" or "shortlink" for the whole group
+                    .col(text(CommandPermission::Command))
+                    // lowercased without underscores, like roster committees
+                    .col(text(CommandPermission::Committee))
+                    .col(big_integer(CommandPermission::AddedBy))
+                    .col(timestamp(CommandPermission::AddedAt).default(Expr::cust("NOW()")))
+                    .primary_key(
+                        Index::create()
+                            .col(CommandPermission::Command)
+                            .col(CommandPermission::Committee),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CommandPermission::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CommandPermission {
+    Table,
+    Command,
+    Committee,
+    AddedBy,
+    AddedAt,
+}
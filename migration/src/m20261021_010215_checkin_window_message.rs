@@ -0,0 +1,39 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CheckinWindow::Table)
+                    // where the code was posted, so the bot can keep it updated after restarting
+                    .add_column(big_integer_null(CheckinWindow::CodeChannelId))
+                    .add_column(big_integer_null(CheckinWindow::CodeMessageId))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CheckinWindow::Table)
+                    .drop_column(CheckinWindow::CodeChannelId)
+                    .drop_column(CheckinWindow::CodeMessageId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CheckinWindow {
+    Table,
+    CodeChannelId,
+    CodeMessageId,
+}
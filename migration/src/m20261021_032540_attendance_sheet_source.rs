@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::extension::postgres::Type;
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_type(
+                Type::alter()
+                    .name(AttendanceSource::Enum)
+                    .add_value(AttendanceSource::Sheet)
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // postgres can't drop enum values, so mark sheet imports as bulk again instead
+        let conn = manager.get_connection();
+        conn.execute_unprepared("UPDATE attendance SET source = 'bulk' WHERE source = 'sheet'")
+            .await?;
+        conn.execute_unprepared("UPDATE attendance_log SET source = 'bulk' WHERE source = 'sheet'")
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AttendanceSource {
+    #[sea_orm(iden = "attendance_source")]
+    Enum,
+    Sheet,
+}
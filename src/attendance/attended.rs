@@ -1,83 +1,257 @@
-use anyhow::Error;
-use chrono::{NaiveDate, NaiveDateTime, Utc};
+use anyhow::{Context as _, Error, bail};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
+use entity::attendance;
+use entity::sea_orm_active_enums::AttendanceSource;
 use itertools::Itertools as _;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, Condition, EntityTrait as _, QueryFilter as _, QueryOrder as _,
+    sea_query::OnConflict,
+};
+use serenity::all::UserId;
 
 use crate::{
     AppContext, AppError, AppVars,
-    util::{ContextExtras as _, gsheets::get_spreadsheet_range},
+    util::{
+        ContextExtras as _,
+        gsheets::{SheetRange, get_spreadsheet_range},
+        time::local_to_utc,
+    },
 };
 
+/// Parses a timestamp from the check-in sheet, which is written in the club's local time, into
+/// naive UTC. Rows without a time are placed at the start of that day.
+pub(crate) fn parse_sheet_timestamp(tz: Tz, time: &str) -> Option<NaiveDateTime> {
+    let time = time.trim();
+    // two digit years go first, since `%Y` would happily read `25` as the year 25
+    let local = NaiveDateTime::parse_from_str(time, "%m/%d/%y %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(time, "%m/%d/%Y %H:%M:%S"))
+        .or_else(|_| {
+            NaiveDate::parse_from_str(time, "%m/%d/%y").map(|res| res.and_time(NaiveTime::MIN))
+        })
+        .or_else(|_| {
+            NaiveDate::parse_from_str(time, "%m/%d/%Y").map(|res| res.and_time(NaiveTime::MIN))
+        })
+        .ok()?;
+
+    Some(local_to_utc(tz, local))
+}
+
 pub(crate) async fn get_events_attended_text(
     data: &AppVars,
-    email: &String,
+    email: Option<&str>,
+    discord_id: UserId,
 ) -> Result<Vec<String>, AppError> {
+    let mut who = Condition::any().add(attendance::Column::DiscordId.eq(discord_id.get() as i64));
+    if let Some(email) = email {
+        who = who.add(attendance::Column::Email.eq(email));
+    }
+
+    let events = attendance::Entity::find()
+        .filter(who)
+        .order_by_asc(attendance::Column::AttendedAt)
+        .all(&data.db)
+        .await
+        .context("fetch attendance")?
+        .into_iter()
+        .map(|row| {
+            format!(
+                "- <t:{}:d> {}",
+                row.attended_at.and_utc().timestamp(),
+                row.event_name.as_deref().unwrap_or("ICSSC Event")
+            )
+        })
+        .collect_vec();
+
+    Ok(events)
+}
+
+/// Copies every row of the check-in sheet into the attendance table, skipping rows already there
+/// and rows marked as undone
+async fn import_attendance_sheet(data: &AppVars) -> anyhow::Result<(usize, usize)> {
     let sheet_id = &data.env.attendance_sheet.id;
     let range = &data.env.attendance_sheet.ranges.checkin;
-    let resp = get_spreadsheet_range(data.google_service_account.clone(), sheet_id, range).await?;
+    if sheet_id.is_empty() || range.is_empty() {
+        bail!("No attendance sheet is configured");
+    }
 
-    let events = resp
+    // also read the column `/attendance undo` marks rows in
+    let range =
+        SheetRange::parse(range).map_or_else(|| range.clone(), |range| range.widened().to_string());
+    let resp = get_spreadsheet_range(data.google_service_account.clone(), sheet_id, &range).await?;
+
+    let (entries, skipped): (Vec<_>, Vec<_>) = resp
         .values
         .into_iter()
-        .filter_map(|row| {
-            let row = row.into_iter().collect_array::<4>()?;
-            let [time, row_email, _, name] = row;
-
-            if row_email != *email {
+        .map(|row| {
+            let mut row = row.into_iter();
+            let [time, email, _, name] = row.by_ref().take(4).collect_array::<4>()?;
+            if row.next().is_some_and(|mark| !mark.is_empty()) {
                 return None;
             }
+            let attended_at = parse_sheet_timestamp(data.timezone, &time)?;
 
-            let current_time = Utc::now().time();
-            let datetime = NaiveDateTime::parse_from_str(&time, "%m/%d/%Y %H:%M:%S")
-                .or_else(|_| NaiveDateTime::parse_from_str(&time, "%m/%d/%y %H:%M:%S"))
-                .or_else(|_| {
-                    NaiveDate::parse_from_str(&time, "%m/%d/%y")
-                        .map(|res| res.and_time(current_time))
-                })
-                .or_else(|_| {
-                    NaiveDate::parse_from_str(&time, "%m/%d/%Y")
-                        .map(|res| res.and_time(current_time))
-                })
-                .ok()?;
-
-            Some(format!("- <t:{}:d> {name}", datetime.and_utc().timestamp()))
+            Some(attendance::ActiveModel {
+                id: ActiveValue::NotSet,
+                email: ActiveValue::Set(email),
+                discord_id: ActiveValue::Set(None),
+                event_name: ActiveValue::Set(Some(name).filter(|name| !name.is_empty())),
+                attended_at: ActiveValue::Set(attended_at),
+                source: ActiveValue::Set(AttendanceSource::Sheet),
+                checkin_window_id: ActiveValue::Set(None),
+            })
         })
-        .collect_vec();
+        .partition(Option::is_some);
 
-    Ok(events)
+    let entries = entries.into_iter().flatten().collect_vec();
+    let total = entries.len();
+
+    let mut inserted = 0;
+    // stay well under the postgres bind parameter limit
+    for chunk in entries.chunks(1000) {
+        inserted += attendance::Entity::insert_many(chunk.to_vec())
+            .on_conflict(
+                OnConflict::columns([
+                    attendance::Column::Email,
+                    attendance::Column::AttendedAt,
+                    attendance::Column::EventName,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&data.db)
+            .await
+            .context("import attendance")?;
+    }
+
+    Ok((inserted as usize, total - inserted as usize + skipped.len()))
 }
 
 /// See what ICSSC events you have checked in for!
 #[poise::command(slash_command, hide_in_help)]
 pub(crate) async fn attended(ctx: AppContext<'_>) -> Result<(), Error> {
-    let Ok(_) = ctx
+    ctx.defer_ephemeral().await?;
+
+    // the roster only adds older entries logged by email, so carry on without it if it's down
+    let email = match ctx
         .data()
-        .google_service_account
+        .roster
         .write()
         .await
-        .get_access_token("https://www.googleapis.com/auth/spreadsheets.readonly")
+        .get_user_from_discord(ctx.author(), false)
         .await
-    else {
-        ctx.reply_ephemeral("Unable to find who you are :(").await?;
-        return Ok(());
+    {
+        Ok(Some(user)) => Some(user.email),
+        _ => None,
     };
 
-    ctx.defer_ephemeral().await?;
-
-    let username = &ctx.author().name;
-    let mut roster = ctx.data().roster.write().await;
-    let Ok(Some(user)) = roster.get_user_from_discord(username, true).await else {
-        ctx.reply_ephemeral(
-            "\
-Cannot find a matching internal member. Double check that your \
-Discord username on the internal roster is correct.",
-        )
-        .await?;
+    let events = get_events_attended_text(ctx.data(), email.as_deref(), ctx.author().id).await?;
+    if events.is_empty() {
+        ctx.reply_ephemeral("You haven't checked in to any events yet!")
+            .await?;
         return Ok(());
-    };
-
-    let events = get_events_attended_text(ctx.data(), &user.email).await?;
+    }
 
     ctx.reply_ephemeral(format!("Events you attended:\n{}", events.join("\n")))
         .await?;
     Ok(())
 }
+
+/// Copy past check-ins from the attendance sheet into the bot's database
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    ephemeral,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn import_sheet(ctx: AppContext<'_>) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+
+    let (inserted, skipped) = import_attendance_sheet(ctx.data()).await?;
+    ctx.reply_ephemeral(format!(
+        "Imported {inserted} check-ins ({skipped} rows were duplicates or unreadable)"
+    ))
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_sheet_timestamp;
+    use chrono::NaiveDateTime;
+    use chrono_tz::America::Los_Angeles;
+
+    fn utc(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn four_digit_year_with_time() {
+        assert_eq!(
+            parse_sheet_timestamp(Los_Angeles, "10/14/2025 18:30:05"),
+            Some(utc("2025-10-15 01:30:05"))
+        );
+    }
+
+    #[test]
+    fn two_digit_year_with_time() {
+        assert_eq!(
+            parse_sheet_timestamp(Los_Angeles, "1/9/25 9:05:00"),
+            Some(utc("2025-01-09 17:05:00"))
+        );
+    }
+
+    #[test]
+    fn date_only_rows_start_at_local_midnight() {
+        assert_eq!(
+            parse_sheet_timestamp(Los_Angeles, "3/4/2025"),
+            Some(utc("2025-03-04 08:00:00"))
+        );
+        assert_eq!(
+            parse_sheet_timestamp(Los_Angeles, "05/06/25"),
+            Some(utc("2025-05-06 07:00:00"))
+        );
+    }
+
+    #[test]
+    fn evening_events_keep_their_local_date() {
+        // 11pm in Irvine is already the next day in UTC
+        let parsed = parse_sheet_timestamp(Los_Angeles, "11/20/2025 23:15:00").unwrap();
+        assert_eq!(parsed, utc("2025-11-21 07:15:00"));
+        assert_eq!(
+            parsed.and_utc().with_timezone(&Los_Angeles).date_naive(),
+            chrono::NaiveDate::from_ymd_opt(2025, 11, 20).unwrap()
+        );
+    }
+
+    #[test]
+    fn daylight_saving_transitions() {
+        // 2:30am doesn't exist on the spring forward date
+        assert_eq!(
+            parse_sheet_timestamp(Los_Angeles, "3/9/2025 2:30:00"),
+            Some(utc("2025-03-09 10:30:00"))
+        );
+        // 1:30am happens twice on the fall back date; the first one is used
+        assert_eq!(
+            parse_sheet_timestamp(Los_Angeles, "11/2/2025 1:30:00"),
+            Some(utc("2025-11-02 08:30:00"))
+        );
+    }
+
+    #[test]
+    fn surrounding_whitespace_is_ignored() {
+        assert_eq!(
+            parse_sheet_timestamp(Los_Angeles, " 10/14/2025 18:30:05\n"),
+            Some(utc("2025-10-15 01:30:05"))
+        );
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        assert_eq!(parse_sheet_timestamp(Los_Angeles, ""), None);
+        assert_eq!(parse_sheet_timestamp(Los_Angeles, "2025-10-14"), None);
+        assert_eq!(parse_sheet_timestamp(Los_Angeles, "not a date"), None);
+        assert_eq!(parse_sheet_timestamp(Los_Angeles, "13/01/2025"), None);
+    }
+}
@@ -1,43 +1,72 @@
-use std::{collections::HashSet, str::FromStr as _};
-
 use anyhow::{Context as _, Error, bail};
+use chrono::Utc;
+use entity::sea_orm_active_enums::AttendanceSource;
+use entity::{attendance, attendance_log, attendance_log_entry};
 use itertools::Itertools as _;
-use serenity::{
-    all::{
-        CacheHttp as _, CreateActionRow, CreateInputText, CreateInteractionResponse, CreateModal,
-        EditInteractionResponse, InputTextStyle, ModalInteraction, ReactionType, UserId,
-    },
-    futures::future,
+use poise::CreateReply;
+use sea_orm::{
+    ActiveModelTrait as _, ActiveValue, ColumnTrait as _, EntityTrait as _, QueryFilter as _,
+};
+use serenity::all::{
+    ChannelId, ComponentInteractionDataKind, CreateActionRow, CreateInputText,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateQuickModal, GuildId,
+    InputTextStyle, Mentionable, MessageId, ReactionType, UserId,
 };
 
 use crate::{
     AppContext, AppError, AppVars,
+    attendance::{
+        record::record_attendance,
+        window::{find_window_for_code, open_windows},
+    },
     util::{
-        ContextExtras as _, message::get_members, modal::ModalInputTexts,
-        roster::check_in_with_email,
+        ContextExtras as _,
+        components::{
+            FLOW_TIMEOUT, confirm_buttons_row, fit_select_users, resolve_guild_members,
+            user_select_row,
+        },
+        message::get_members,
     },
 };
 
 /// Check into today's ICSSC event!
 #[poise::command(slash_command, hide_in_help)]
-pub(crate) async fn checkin(ctx: AppContext<'_>) -> Result<(), Error> {
-    let Ok(_) = ctx
-        .data()
-        .google_service_account
-        .write()
-        .await
-        .get_access_token("https://www.googleapis.com/auth/spreadsheets.readonly")
-        .await
-    else {
-        ctx.reply_ephemeral("Unable to find who you are :(").await?;
-        return Ok(());
-    };
-
+pub(crate) async fn checkin(
+    ctx: AppContext<'_>,
+    #[description = "Check-in code shown at the event"] code: Option<String>,
+) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
+    let conn = &ctx.data().db;
+    // in DMs, only a window in the ICSSC server asks for a code
+    let guild_id = ctx
+        .guild_id()
+        .unwrap_or(GuildId::new(ctx.data().channels.icssc_guild_id));
+
+    let window = match code {
+        Some(code) => {
+            let Some(window) = find_window_for_code(conn, &code).await? else {
+                ctx.reply_ephemeral("That check-in code is invalid or has expired")
+                    .await?;
+                return Ok(());
+            };
+            Some(window)
+        }
+        None if !open_windows(conn, Some(guild_id), Utc::now().naive_utc())
+            .await?
+            .is_empty() =>
+        {
+            ctx.reply_ephemeral(
+                "Check-in is open for an event right now; \
+                please use the code shown at the event with `/checkin code:`",
+            )
+            .await?;
+            return Ok(());
+        }
+        None => None,
+    };
 
-    let username = &ctx.author().name;
     let mut roster = ctx.data().roster.write().await;
-    let Ok(Some(user)) = roster.get_user_from_discord(username, false).await else {
+    let Ok(Some(user)) = roster.get_user_from_discord(ctx.author(), false).await else {
         ctx.reply_ephemeral(
             "\
 Cannot find a matching internal member. Double check that your \
@@ -47,131 +76,331 @@ Discord username on the internal roster is correct.",
         return Ok(());
     };
 
-    let Ok(_) = check_in_with_email(ctx.data(), &user.email, None).await else {
+    if let Some(window) = &window
+        && attendance::Entity::find()
+            .filter(attendance::Column::CheckinWindowId.eq(window.id))
+            .filter(attendance::Column::Email.eq(&user.email))
+            .one(conn)
+            .await?
+            .is_some()
+    {
+        ctx.reply_ephemeral(format!(
+            "You are already checked in to {}",
+            window.event_name
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let Ok(_) = record_attendance(
+        ctx.data(),
+        &user.email,
+        Some(ctx.author().id),
+        window.as_ref().map(|window| window.event_name.as_str()),
+        AttendanceSource::Checkin,
+        window.as_ref().map(|window| window.id),
+    )
+    .await
+    else {
         ctx.reply_ephemeral("Unable to check in").await?;
         return Ok(());
     };
 
-    ctx.reply_ephemeral(format!("Successfully checked in as {}", user.name))
-        .await?;
+    ctx.reply_ephemeral(match window {
+        Some(window) => format!(
+            "Successfully checked in to {} as {}",
+            window.event_name, user.name
+        ),
+        None => format!("Successfully checked in as {}", user.name),
+    })
+    .await?;
     Ok(())
 }
 
+fn attendance_components(members: &[UserId]) -> Vec<CreateActionRow> {
+    vec![
+        user_select_row("attendance_log_members", "Who was at this event?", members),
+        confirm_buttons_row("attendance_log"),
+    ]
+}
+
 /// Count a message as attendance for an ICSSC event
 #[poise::command(context_menu_command = "Log Attendance", guild_only)]
 pub(crate) async fn log_attendance(
     ctx: AppContext<'_>,
     message: serenity::all::Message,
 ) -> Result<(), Error> {
-    let AppContext::Application(ctx) = ctx else {
-        bail!("unexpected context type")
-    };
-
-    let data = ctx.data();
-    let is_matchy_channel = data.channels.matchy_channel_id == message.channel_id.get();
+    let is_matchy_channel = ctx.data().channels.matchy_channel_id == message.channel_id.get();
     let default_event_name = match is_matchy_channel {
         true => "Matchy Meetup",
         false => "",
     };
 
-    let members: HashSet<String> = get_members(&message, true);
+    let logged = pick_and_log_attendance(
+        ctx,
+        format!("Logging attendance for {}", message.link()),
+        get_members(&message, true),
+        default_event_name,
+        Some((message.channel_id, message.id)),
+    )
+    .await?;
 
-    // create inputs
-    let msg_input = CreateActionRow::InputText(
-        CreateInputText::new(InputTextStyle::Short, "Message ID", "message_id")
-            .value(message.id.to_string())
-            .required(true),
-    );
-    let event_name_input = CreateActionRow::InputText(
-        CreateInputText::new(InputTextStyle::Short, "Name of Event", "event_name")
-            .value(default_event_name)
-            .required(false),
-    );
-    let members_input = CreateActionRow::InputText(
-        CreateInputText::new(
-            InputTextStyle::Paragraph,
-            "Who was at this event?",
-            "participants",
+    if logged {
+        let _ = message
+            .react(ctx.http(), ReactionType::Unicode("👋".to_owned()))
+            .await;
+    }
+
+    Ok(())
+}
+
+/// Lets the invoking board member adjust a prefilled member picker and name the event before
+/// recording attendance. Returns whether anything was recorded.
+pub(crate) async fn pick_and_log_attendance(
+    ctx: AppContext<'_>,
+    heading: String,
+    mut members: Vec<UserId>,
+    default_event_name: &str,
+    message: Option<(ChannelId, MessageId)>,
+) -> Result<bool, AppError> {
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
+    let heading = match fit_select_users(&mut members) {
+        Some(note) => format!("{heading}\n{note}; log them separately"),
+        None => heading,
+    };
+
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(heading)
+                .components(attendance_components(&members))
+                .ephemeral(true),
         )
-        .value(members.iter().join("\n"))
-        .required(true),
-    );
+        .await?;
+    let sent = handle.message().await?;
 
-    let modal = CreateModal::new("attendance_log_modal_confirm", "Confirm Attendance")
-        .components(vec![msg_input, event_name_input, members_input]);
+    while let Some(ixn) = sent
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(FLOW_TIMEOUT)
+        .await
+    {
+        match (ixn.data.custom_id.as_str(), &ixn.data.kind) {
+            ("attendance_log_members", ComponentInteractionDataKind::UserSelect { values }) => {
+                members.clone_from(values);
+                ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+                    .await?;
+            }
+            ("attendance_log_confirm", _) => {
+                let event_name_input =
+                    CreateInputText::new(InputTextStyle::Short, "Name of Event", "event_name")
+                        .value(default_event_name)
+                        .required(false);
+                let Some(modal) = ixn
+                    .quick_modal(
+                        ctx.serenity_context(),
+                        CreateQuickModal::new("Confirm Attendance")
+                            .field(event_name_input)
+                            .timeout(FLOW_TIMEOUT),
+                    )
+                    .await?
+                else {
+                    continue;
+                };
+                modal
+                    .interaction
+                    .create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+                    .await?;
 
-    let reply = CreateInteractionResponse::Modal(modal);
-    ctx.interaction.create_response(ctx.http(), reply).await?;
+                let event_name = modal
+                    .inputs
+                    .into_iter()
+                    .next()
+                    .filter(|name| !name.trim().is_empty());
 
-    Ok(())
-}
+                let origin = LogOrigin {
+                    guild_id,
+                    logged_by: ctx.author().id,
+                    message,
+                };
+                let logged = confirm_attendance_log(
+                    ctx.serenity_context(),
+                    ctx.data(),
+                    &origin,
+                    &members,
+                    event_name.as_deref(),
+                    AttendanceSource::Log,
+                    false,
+                )
+                .await;
 
-pub(crate) async fn confirm_attendance_log_modal(
-    ctx: &serenity::all::Context,
-    data: &'_ AppVars,
-    ixn: &ModalInteraction,
-) -> Result<(), AppError> {
-    let inputs = ModalInputTexts::new(ixn);
-    let message = inputs
-        .get_required_value("message_id")?
-        .parse::<u64>()
-        .context("unexpected non-numerical message ID")
-        .map(|id| ixn.channel_id.message(ctx.http(), id))?
+                let (content, components, done) = match logged {
+                    Ok(content) => (content, vec![], true),
+                    Err(why) => (why.to_string(), attendance_components(&members), false),
+                };
+                handle
+                    .edit(
+                        ctx,
+                        CreateReply::default()
+                            .content(content)
+                            .components(components),
+                    )
+                    .await?;
+
+                if done {
+                    return Ok(true);
+                }
+            }
+            ("attendance_log_cancel", _) => {
+                ixn.create_response(
+                    ctx.http(),
+                    CreateInteractionResponse::UpdateMessage(
+                        CreateInteractionResponseMessage::new()
+                            .content("Cancelled, no attendance was logged")
+                            .components(vec![]),
+                    ),
+                )
+                .await?;
+                return Ok(false);
+            }
+            _ => {
+                ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+                    .await?;
+            }
+        }
+    }
+
+    handle
+        .edit(
+            ctx,
+            CreateReply::default()
+                .content("Timed out, no attendance was logged")
+                .components(vec![]),
+        )
         .await?;
 
-    let attendees = inputs.get_required_value("participants")?;
-    let event_name = inputs.get_value("event_name")?;
+    Ok(false)
+}
 
-    ixn.defer_ephemeral(ctx.http()).await?;
+/// Where a bulk attendance log came from, kept in its audit trail
+pub(crate) struct LogOrigin {
+    pub(crate) guild_id: GuildId,
+    pub(crate) logged_by: UserId,
+    pub(crate) message: Option<(ChannelId, MessageId)>,
+}
 
-    let participant_ids = attendees.split('\n');
-    let participants = future::join_all(participant_ids.clone().filter_map(|s| {
-        let uid = UserId::from_str(s.trim()).ok()?;
-        Some(ixn.guild_id?.member(ctx.http(), uid))
-    }))
-    .await
-    .into_iter()
-    .collect::<Result<Vec<_>, _>>()
-    .context("Some user IDs not found")?;
+/// Records attendance for each of the selected users. Unless `skip_unrostered` is set, every user
+/// must be on the roster; otherwise users who aren't are listed in the response and not recorded.
+/// The log and its result for each user are saved so it can be reviewed and undone later.
+pub(crate) async fn confirm_attendance_log(
+    ctx: &serenity::all::Context,
+    data: &AppVars,
+    origin: &LogOrigin,
+    user_ids: &[UserId],
+    event_name: Option<&str>,
+    source: AttendanceSource,
+    skip_unrostered: bool,
+) -> Result<String, AppError> {
+    let participants = resolve_guild_members(ctx, origin.guild_id, user_ids).await?;
 
-    let usernames = participants
-        .iter()
-        .map(|member| member.user.name.as_str())
-        .collect_vec();
+    let users = participants.iter().map(|member| &member.user).collect_vec();
 
     let members = data
         .roster
         .write()
         .await
-        .get_users_from_discord(&usernames, true)
+        .get_users_from_discord(&users, true)
         .await?;
-    let is_missing = members.len() != usernames.len();
-    if is_missing {
-        bail!("user lookup failed");
+
+    let unrostered = participants
+        .iter()
+        .map(|participant| participant.user.id)
+        .filter(|user_id| !members.iter().any(|(id, _)| id == user_id))
+        .collect_vec();
+    let not_on_roster = unrostered.iter().map(Mentionable::mention).collect_vec();
+    if !not_on_roster.is_empty() && !skip_unrostered {
+        bail!(
+            "The following users are not on the internal roster: {}",
+            not_on_roster.iter().join(", ")
+        );
     }
 
+    let log = attendance_log::ActiveModel {
+        id: ActiveValue::NotSet,
+        guild_id: ActiveValue::Set(origin.guild_id.get() as i64),
+        logged_by: ActiveValue::Set(origin.logged_by.get() as i64),
+        channel_id: ActiveValue::Set(origin.message.map(|(channel, _)| channel.get() as i64)),
+        message_id: ActiveValue::Set(origin.message.map(|(_, message)| message.get() as i64)),
+        event_name: ActiveValue::Set(event_name.map(str::to_owned)),
+        source: ActiveValue::Set(source.clone()),
+        logged_at: ActiveValue::Set(Utc::now().naive_utc()),
+        undone_at: ActiveValue::Set(None),
+        undone_by: ActiveValue::Set(None),
+    }
+    .insert(&data.db)
+    .await
+    .context("insert attendance log")?;
+
+    let mut entries = Vec::new();
     let mut response_lines = Vec::new();
-    for member in members {
-        let success = check_in_with_email(data, &member.email, event_name.as_deref())
-            .await
-            .is_ok();
-        let emoji = match success {
-            true => "☑️",
-            false => "❌",
+    for (user_id, member) in members {
+        let discord_id = Some(user_id);
+        let recorded = record_attendance(
+            data,
+            &member.email,
+            discord_id,
+            event_name,
+            source.clone(),
+            None,
+        )
+        .await;
+        let emoji = match recorded {
+            Ok(_) => "☑️",
+            Err(_) => "❌",
         };
         let line = format!("{} {} ({})", emoji, member.name, member.email);
         response_lines.push(line);
-    }
-
-    let content = String::from("Submitted attendance for the following users:\n")
-        + &response_lines.join("\n");
 
-    ixn.edit_response(ctx.http(), EditInteractionResponse::new().content(content))
-        .await?;
+        entries.push(attendance_log_entry::ActiveModel {
+            id: ActiveValue::NotSet,
+            log_id: ActiveValue::Set(log.id),
+            discord_id: ActiveValue::Set(discord_id.map(|id| id.get() as i64)),
+            email: ActiveValue::Set(Some(member.email)),
+            member_name: ActiveValue::Set(Some(member.name)),
+            attendance_id: ActiveValue::Set(recorded.as_ref().ok().map(|row| row.id)),
+            recorded: ActiveValue::Set(recorded.is_ok()),
+        });
+    }
+    entries.extend(
+        unrostered
+            .iter()
+            .map(|id| attendance_log_entry::ActiveModel {
+                id: ActiveValue::NotSet,
+                log_id: ActiveValue::Set(log.id),
+                discord_id: ActiveValue::Set(Some(id.get() as i64)),
+                email: ActiveValue::Set(None),
+                member_name: ActiveValue::Set(None),
+                attendance_id: ActiveValue::Set(None),
+                recorded: ActiveValue::Set(false),
+            }),
+    );
+    if !entries.is_empty() {
+        attendance_log_entry::Entity::insert_many(entries)
+            .exec_without_returning(&data.db)
+            .await
+            .context("insert attendance log entries")?;
+    }
 
-    let _ = message
-        .react(ctx.http(), ReactionType::Unicode("👋".to_owned()))
-        .await;
+    let mut response = format!(
+        "Recorded attendance for the following users (log #{}):\n{}",
+        log.id,
+        response_lines.join("\n")
+    );
+    if !not_on_roster.is_empty() {
+        response += "\n\nNot on the internal roster, so not recorded: ";
+        response += &not_on_roster.iter().join(", ");
+    }
+    response += "\n\nIf this was a mistake, it can be undone with `/attendance undo`";
 
-    Ok(())
+    Ok(response)
 }
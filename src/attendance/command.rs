@@ -0,0 +1,34 @@
+use crate::AppContext;
+use crate::AppError;
+use crate::attendance::attended::import_sheet;
+use crate::attendance::event::event;
+use crate::attendance::leaderboard::leaderboard;
+use crate::attendance::logs::{logs, undo};
+use crate::attendance::requirements::{requirements, status};
+use crate::attendance::rsvp::{from_event, rsvp_report};
+use crate::attendance::voice::log_voice;
+use crate::attendance::window::{close_checkin, open_checkin};
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands(
+        "status",
+        "leaderboard",
+        "event",
+        "open_checkin",
+        "close_checkin",
+        "log_voice",
+        "from_event",
+        "logs",
+        "undo",
+        "rsvp_report",
+        "requirements",
+        "import_sheet"
+    ),
+    guild_only
+)]
+pub(crate) async fn attendance(ctx: AppContext<'_>) -> Result<(), AppError> {
+    ctx.reply("base command is a noop").await?;
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use anyhow::Context as _;
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
+use entity::attendance;
+use itertools::Itertools as _;
+use poise::CreateReply;
+use sea_orm::{
+    ColumnTrait as _, DatabaseConnection, EntityTrait as _, QueryFilter as _, QueryOrder as _,
+    QuerySelect as _,
+    sea_query::{Expr, Func},
+};
+use serenity::all::CreateEmbed;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    AppContext, AppError,
+    attendance::period::AttendancePeriod,
+    util::{ContextExtras as _, time},
+};
+
+pub(crate) async fn event_name_choices(ctx: AppContext<'_>, partial: &str) -> Vec<String> {
+    let names = attendance::Entity::find()
+        .select_only()
+        .column(attendance::Column::EventName)
+        .filter(attendance::Column::EventName.is_not_null())
+        .filter(
+            Expr::expr(Func::lower(Expr::col(attendance::Column::EventName)))
+                .like(format!("%{}%", partial.to_lowercase())),
+        )
+        .group_by(attendance::Column::EventName)
+        .order_by_desc(Expr::col(attendance::Column::AttendedAt).max())
+        .limit(25)
+        .into_tuple::<String>()
+        .all(&ctx.data().db)
+        .await;
+
+    names.unwrap_or_default()
+}
+
+/// Attendance under an event name, matched case-insensitively
+async fn event_attendance(
+    conn: &DatabaseConnection,
+    name: &str,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> anyhow::Result<Vec<attendance::Model>> {
+    attendance::Entity::find()
+        .filter(
+            Expr::expr(Func::lower(Expr::col(attendance::Column::EventName)))
+                .eq(name.trim().to_lowercase()),
+        )
+        .filter(attendance::Column::AttendedAt.gte(start))
+        .filter(attendance::Column::AttendedAt.lt(end))
+        .all(conn)
+        .await
+        .context("fetch event attendance")
+}
+
+struct EventStats {
+    /// Lowercased emails of everyone who attended
+    attendees: HashSet<String>,
+    check_ins: usize,
+    /// Distinct local days the event was held on
+    sessions: usize,
+}
+
+fn event_stats(tz: Tz, rows: &[attendance::Model]) -> EventStats {
+    EventStats {
+        attendees: rows.iter().map(|row| row.email.to_lowercase()).collect(),
+        check_ins: rows.len(),
+        sessions: rows
+            .iter()
+            .map(|row| time::from_utc(tz, row.attended_at).date_naive())
+            .unique()
+            .count(),
+    }
+}
+
+/// Attendees whose first attendance of any event was at this event
+async fn first_timers(
+    conn: &DatabaseConnection,
+    rows: &[attendance::Model],
+) -> anyhow::Result<usize> {
+    let mut first_here = HashMap::<String, NaiveDateTime>::new();
+    for row in rows {
+        let first = first_here
+            .entry(row.email.to_lowercase())
+            .or_insert(row.attended_at);
+        *first = (*first).min(row.attended_at);
+    }
+
+    let mut first_ever = HashMap::<String, NaiveDateTime>::new();
+    let earliest = attendance::Entity::find()
+        .select_only()
+        .column(attendance::Column::Email)
+        .column_as(Expr::col(attendance::Column::AttendedAt).min(), "first")
+        .filter(
+            Expr::expr(Func::lower(Expr::col(attendance::Column::Email)))
+                .is_in(first_here.keys().cloned()),
+        )
+        .group_by(attendance::Column::Email)
+        .into_tuple::<(String, NaiveDateTime)>()
+        .all(conn)
+        .await
+        .context("fetch first attendance")?;
+    // the same member may have rows under differently cased emails
+    for (email, first) in earliest {
+        let entry = first_ever.entry(email.to_lowercase()).or_insert(first);
+        *entry = (*entry).min(first);
+    }
+
+    Ok(first_here
+        .iter()
+        .filter(|(email, first)| first_ever.get(*email).is_none_or(|ever| ever >= first))
+        .count())
+}
+
+fn percent_change(now: usize, before: usize) -> String {
+    match before {
+        0 => String::from("\u{2013}"),
+        before => format!(
+            "{:+.0}%",
+            100.0 * (now as f64 - before as f64) / before as f64
+        ),
+    }
+}
+
+/// Attendance breakdown for an event: attendees, first-timers, committees and the previous period
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    guild_only,
+    ephemeral,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn event(
+    ctx: AppContext<'_>,
+    #[description = "Event name, as entered when attendance was taken"]
+    #[autocomplete = "event_name_choices"]
+    name: String,
+    #[description = "Time period to report on; defaults to this quarter"] period: Option<
+        AttendancePeriod,
+    >,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    let conn = &ctx.data().db;
+    let tz = ctx.data().timezone;
+    let today = time::today(tz);
+
+    let period = period.unwrap_or(AttendancePeriod::ThisQuarter);
+    let (start, end, period_name) = period.bounds(tz, today);
+    let (prev_start, prev_end, prev_name) = period.previous_bounds(tz, today);
+
+    let rows = event_attendance(conn, &name, start, end).await?;
+    if rows.is_empty() {
+        ctx.reply_ephemeral(format!(
+            "No attendance was recorded for **{}** in {period_name}",
+            name.trim()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let stats = event_stats(tz, &rows);
+    let prev = event_stats(
+        tz,
+        &event_attendance(conn, &name, prev_start, prev_end).await?,
+    );
+    let first_timers = first_timers(conn, &rows).await?;
+
+    let roster = ctx.data().roster.write().await.fetch(60).await?.clone();
+    let mut not_rostered = 0;
+    let mut committees = HashMap::<&str, usize>::new();
+    for email in &stats.attendees {
+        let Some(row) = roster
+            .iter()
+            .find(|row| row.email.eq_ignore_ascii_case(email))
+        else {
+            not_rostered += 1;
+            continue;
+        };
+        for committee in row.committees.iter().filter(|c| !c.is_empty()) {
+            *committees.entry(committee).or_default() += 1;
+        }
+    }
+    let mut committee_lines = committees
+        .into_iter()
+        .sorted_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)))
+        .map(|(committee, count)| format!("`{committee}`: {count}"))
+        .collect_vec();
+    if not_rostered > 0 {
+        committee_lines.push(format!("Not on roster: {not_rostered}"));
+    }
+    if committee_lines.is_empty() {
+        committee_lines.push(String::from("\u{2013}"));
+    }
+
+    let comparison = match prev.check_ins {
+        0 => format!("No attendance was recorded in {prev_name}"),
+        _ => format!(
+            "{} attendees over {} sessions ({} now)",
+            prev.attendees.len(),
+            prev.sessions,
+            percent_change(stats.attendees.len(), prev.attendees.len()),
+        ),
+    };
+
+    let embed = CreateEmbed::default()
+        .color(0xff87a6)
+        .title(format!("{} ({period_name})", name.trim()))
+        .field("Attendees", stats.attendees.len().to_string(), true)
+        .field("Check-ins", stats.check_ins.to_string(), true)
+        .field("Sessions", stats.sessions.to_string(), true)
+        .field(
+            "First-timers",
+            format!(
+                "{first_timers} ({:.0}%)",
+                100.0 * first_timers as f64 / stats.attendees.len() as f64
+            ),
+            true,
+        )
+        .field(
+            "Average per session",
+            format!("{:.1}", stats.check_ins as f64 / stats.sessions as f64),
+            true,
+        )
+        .field("Committees", committee_lines.join("\n"), false)
+        .field(format!("Compared to {prev_name}"), comparison, false);
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
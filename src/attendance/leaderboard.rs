@@ -0,0 +1,161 @@
+use anyhow::Context as _;
+use entity::attendance;
+use itertools::Itertools as _;
+use sea_orm::{
+    ColumnTrait as _, EntityTrait as _, QueryFilter as _, QuerySelect as _,
+    sea_query::{Expr, Func},
+};
+use serenity::all::CreateEmbed;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+
+use crate::{
+    AppContext, AppError,
+    attendance::period::AttendancePeriod,
+    util::{
+        ContextExtras as _,
+        paginate::{EmbedLinePaginator, PaginatorOptions},
+        roster::RosterSheetRow,
+        time,
+    },
+};
+
+pub(crate) async fn committee_choices(ctx: AppContext<'_>, partial: &str) -> Vec<String> {
+    let mut roster = ctx.data().roster.write().await;
+    let Ok(rows) = roster.fetch(60).await else {
+        return vec![];
+    };
+
+    let partial = partial.to_lowercase();
+    rows.iter()
+        .flat_map(|row| row.committees.iter())
+        .filter(|committee| !committee.is_empty() && committee.contains(&partial))
+        .unique()
+        .sorted()
+        .take(25)
+        .cloned()
+        .collect_vec()
+}
+
+/// Number of events each email (lowercased) attended in the given period. Checking in to the
+/// same event more than once on the same day only counts once.
+pub(crate) async fn attendance_counts(
+    ctx: AppContext<'_>,
+    period: AttendancePeriod,
+) -> anyhow::Result<(HashMap<String, i64>, String)> {
+    let tz = ctx.data().timezone;
+    let (start, end, period_name) = period.bounds(tz, time::today(tz));
+
+    let email = Expr::expr(Func::lower(Expr::col(attendance::Column::Email)));
+    let counts = attendance::Entity::find()
+        .select_only()
+        .column_as(email.clone(), "email")
+        .column_as(
+            Expr::cust_with_values(
+                "COUNT(DISTINCT (LOWER(COALESCE(event_name, '')), \
+                (attended_at AT TIME ZONE 'UTC' AT TIME ZONE $1)::date))",
+                [tz.name()],
+            ),
+            "count",
+        )
+        .filter(attendance::Column::AttendedAt.gte(start))
+        .filter(attendance::Column::AttendedAt.lt(end))
+        .group_by(email)
+        .into_tuple::<(String, i64)>()
+        .all(&ctx.data().db)
+        .await
+        .context("count attendance")?
+        .into_iter()
+        .collect();
+
+    Ok((counts, period_name))
+}
+
+/// Rank internal members by the number of events they attended
+#[poise::command(slash_command, guild_only)]
+pub(crate) async fn leaderboard(
+    ctx: AppContext<'_>,
+    #[description = "Time period to count; defaults to this quarter"] period: Option<
+        AttendancePeriod,
+    >,
+    #[description = "Only show members of this committee"]
+    #[autocomplete = "committee_choices"]
+    committee: Option<String>,
+    #[description = "Board only: list the members who attended fewer than this many events"]
+    below_threshold: Option<u32>,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+
+    let period = period.unwrap_or(AttendancePeriod::ThisQuarter);
+    let committee = committee.map(|committee| committee.to_lowercase().replace('_', ""));
+
+    let members = {
+        let mut roster = ctx.data().roster.write().await;
+        let roster = roster.snapshot(60).await?;
+
+        if below_threshold.is_some()
+            && !roster
+                .find(ctx.author().id, &ctx.author().name)
+                .is_some_and(RosterSheetRow::is_board)
+        {
+            ctx.reply_ephemeral("Only board members can view who is below a threshold")
+                .await?;
+            return Ok(());
+        }
+
+        roster
+            .rows
+            .iter()
+            .filter(|row| {
+                committee
+                    .as_ref()
+                    .is_none_or(|committee| row.committees.contains(committee))
+            })
+            .cloned()
+            .collect_vec()
+    };
+
+    let (counts, period_name) = attendance_counts(ctx, period).await?;
+    let count_of =
+        |row: &RosterSheetRow| counts.get(&row.email.to_lowercase()).copied().unwrap_or(0);
+
+    let ranked = members
+        .iter()
+        .map(|row| (row, count_of(row)))
+        .filter(|(_, count)| below_threshold.is_none_or(|threshold| *count < i64::from(threshold)))
+        .sorted_by(|(a_row, a), (b_row, b)| b.cmp(a).then_with(|| a_row.name.cmp(&b_row.name)))
+        .collect_vec();
+
+    if ranked.is_empty() {
+        ctx.reply_ephemeral("No members match those filters")
+            .await?;
+        return Ok(());
+    }
+
+    let lines = ranked
+        .iter()
+        .enumerate()
+        .map(|(i, (row, count))| format!("{}. {}: {count}", i + 1, row.name).into_boxed_str())
+        .collect_vec();
+
+    let heading = match below_threshold {
+        Some(threshold) => format!("Below {threshold} Events"),
+        None => String::from("Attendance Leaderboard"),
+    };
+    let title = match &committee {
+        Some(committee) => format!("{heading} ({period_name}, {committee})"),
+        None => format!("{heading} ({period_name})"),
+    };
+
+    let paginator = EmbedLinePaginator::new(
+        lines,
+        PaginatorOptions::default()
+            .sep("\n".into())
+            .max_lines(NonZeroUsize::new(15).unwrap())
+            .ephemeral(true)
+            .embed(CreateEmbed::default().color(0xff87a6).title(title)),
+    );
+
+    paginator.run(ctx).await.context("attendance paginate")?;
+    Ok(())
+}
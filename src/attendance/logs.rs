@@ -0,0 +1,334 @@
+use anyhow::{Context as _, bail};
+use chrono::{TimeDelta, Utc};
+use entity::{attendance, attendance_log, attendance_log_entry};
+use itertools::Itertools as _;
+use poise::CreateReply;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, DbErr, EntityTrait as _, IntoActiveModel as _, QueryFilter as _,
+    QueryOrder as _, TransactionTrait as _,
+};
+use serenity::all::{
+    CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, Mentionable as _,
+    User, UserId,
+};
+use std::num::NonZeroUsize;
+
+use crate::{
+    AppContext, AppError, AppVars,
+    attendance::attended::parse_sheet_timestamp,
+    util::{
+        ContextExtras as _,
+        components::{FLOW_TIMEOUT, confirm_buttons_row},
+        gsheets::{SheetRange, get_spreadsheet_range, set_spreadsheet_cells},
+        paginate::{EmbedLinePaginator, PaginatorOptions},
+    },
+};
+
+/// How far apart a form submission's sheet timestamp and its database row can be
+const MIRROR_TOLERANCE: TimeDelta = TimeDelta::minutes(2);
+
+fn message_link(log: &attendance_log::Model) -> Option<String> {
+    Some(format!(
+        "https://discord.com/channels/{}/{}/{}",
+        log.guild_id, log.channel_id?, log.message_id?
+    ))
+}
+
+fn log_summary(log: &attendance_log::Model, entries: &[attendance_log_entry::Model]) -> String {
+    let recorded = entries.iter().filter(|entry| entry.recorded).count();
+    let not_recorded = match entries.len() - recorded {
+        0 => String::new(),
+        count => format!(", {count} not recorded"),
+    };
+    let link = message_link(log)
+        .map(|link| format!(" ([message]({link}))"))
+        .unwrap_or_default();
+    let summary = format!(
+        "`#{}` <t:{}:f> **{}** by {}: {recorded} recorded{not_recorded}{link}",
+        log.id,
+        log.logged_at.and_utc().timestamp(),
+        log.event_name.as_deref().unwrap_or("Unnamed event"),
+        UserId::new(log.logged_by as u64).mention(),
+    );
+    match log.undone_at {
+        Some(_) => format!("~~{summary}~~ (undone)"),
+        None => summary,
+    }
+}
+
+fn entry_line(entry: &attendance_log_entry::Model) -> String {
+    let mention = entry
+        .discord_id
+        .map(|id| UserId::new(id as u64).mention().to_string())
+        .unwrap_or_default();
+    match (&entry.email, &entry.member_name) {
+        (Some(email), Some(name)) if entry.recorded => format!("☑️ {name} ({email}) {mention}"),
+        (Some(email), Some(name)) => format!("❌ {name} ({email}) {mention}: failed to record"),
+        _ => format!("⚠️ {mention}: not on the internal roster"),
+    }
+}
+
+/// Review bulk attendance logs, or each member's result in one log
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    guild_only,
+    ephemeral,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn logs(
+    ctx: AppContext<'_>,
+    #[description = "Show each member's result for this log number"] log: Option<i32>,
+    #[description = "Only logs made by this board member"] logged_by: Option<User>,
+) -> Result<(), AppError> {
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
+    ctx.defer_ephemeral().await?;
+
+    let mut query = attendance_log::Entity::find()
+        .filter(attendance_log::Column::GuildId.eq(guild_id.get() as i64));
+    if let Some(log) = log {
+        query = query.filter(attendance_log::Column::Id.eq(log));
+    }
+    if let Some(logged_by) = logged_by {
+        query = query.filter(attendance_log::Column::LoggedBy.eq(logged_by.id.get() as i64));
+    }
+
+    let logs = query
+        .order_by_desc(attendance_log::Column::Id)
+        .find_with_related(attendance_log_entry::Entity)
+        .all(&ctx.data().db)
+        .await
+        .context("fetch attendance logs")?;
+
+    if logs.is_empty() {
+        ctx.reply_ephemeral("No attendance logs match those filters")
+            .await?;
+        return Ok(());
+    }
+
+    let (embed, lines) = match (log, logs.as_slice()) {
+        (Some(_), [(log, entries)]) => {
+            let mut embed = CreateEmbed::default()
+                .color(0xff87a6)
+                .title(format!("Attendance Log #{}", log.id))
+                .field("Summary", log_summary(log, entries), false);
+            if let (Some(undone_at), Some(undone_by)) = (log.undone_at, log.undone_by) {
+                embed = embed.field(
+                    "Undone",
+                    format!(
+                        "<t:{}:f> by {}",
+                        undone_at.and_utc().timestamp(),
+                        UserId::new(undone_by as u64).mention()
+                    ),
+                    false,
+                );
+            }
+            (embed, entries.iter().map(entry_line).collect_vec())
+        }
+        _ => (
+            CreateEmbed::default()
+                .color(0xff87a6)
+                .title("Attendance Logs"),
+            logs.iter()
+                .map(|(log, entries)| log_summary(log, entries))
+                .collect_vec(),
+        ),
+    };
+
+    let paginator = EmbedLinePaginator::new(
+        lines.into_iter().map(String::into_boxed_str).collect_vec(),
+        PaginatorOptions::default()
+            .sep("\n".into())
+            .max_lines(NonZeroUsize::new(15).unwrap())
+            .ephemeral(true)
+            .embed(embed),
+    );
+
+    paginator
+        .run(ctx)
+        .await
+        .context("attendance logs paginate")?;
+    Ok(())
+}
+
+/// Marks the check-in sheet rows that the attendance form mirrored `removed` into, so they read as
+/// undone and are skipped by `/attendance import_sheet`. Returns how many rows were marked.
+async fn mark_sheet_rows(
+    data: &AppVars,
+    log_id: i32,
+    removed: &[attendance::Model],
+) -> anyhow::Result<usize> {
+    let sheet_id = &data.env.attendance_sheet.id;
+    let Some(range) = SheetRange::parse(&data.env.attendance_sheet.ranges.checkin) else {
+        return Ok(0);
+    };
+    if data.env.attendance_form.id.is_empty() || sheet_id.is_empty() || removed.is_empty() {
+        return Ok(0);
+    }
+
+    let rows = get_spreadsheet_range(
+        data.google_service_account.clone(),
+        sheet_id,
+        &range.to_string(),
+    )
+    .await?
+    .values;
+
+    let mark = format!("Undone (log #{log_id})");
+    let cells = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| {
+            let (Some(time), Some(email)) = (row.first(), row.get(1)) else {
+                return false;
+            };
+            let Some(time) = parse_sheet_timestamp(data.timezone, time) else {
+                return false;
+            };
+            removed.iter().any(|row| {
+                row.email.eq_ignore_ascii_case(email)
+                    && (row.attended_at - time).abs() <= MIRROR_TOLERANCE
+            })
+        })
+        .map(|(offset, _)| (range.cell(range.end_col + 1, offset), mark.clone()))
+        .collect_vec();
+
+    if !cells.is_empty() {
+        set_spreadsheet_cells(data.google_service_account.clone(), sheet_id, &cells).await?;
+    }
+
+    Ok(cells.len())
+}
+
+/// Undo a bulk attendance log, removing the attendance it recorded
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    guild_only,
+    ephemeral,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn undo(
+    ctx: AppContext<'_>,
+    #[description = "Log number, shown when attendance was logged and in /attendance logs"]
+    log: i32,
+) -> Result<(), AppError> {
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
+    let conn = &ctx.data().db;
+
+    let Some((log, entries)) = attendance_log::Entity::find_by_id(log)
+        .filter(attendance_log::Column::GuildId.eq(guild_id.get() as i64))
+        .find_with_related(attendance_log_entry::Entity)
+        .all(conn)
+        .await
+        .context("fetch attendance log")?
+        .into_iter()
+        .next()
+    else {
+        bail!("There is no attendance log #{log} in this server");
+    };
+    if log.undone_at.is_some() {
+        bail!("Attendance log #{} was already undone", log.id);
+    }
+
+    let attendance_ids = entries
+        .iter()
+        .filter_map(|entry| entry.attendance_id)
+        .collect_vec();
+    let removed = attendance::Entity::find()
+        .filter(attendance::Column::Id.is_in(attendance_ids))
+        .all(conn)
+        .await
+        .context("fetch logged attendance")?;
+
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(format!(
+                    "This will undo attendance log #{} for **{}**, removing {} attendance records.",
+                    log.id,
+                    log.event_name.as_deref().unwrap_or("Unnamed event"),
+                    removed.len()
+                ))
+                .components(vec![confirm_buttons_row("attendance_undo")])
+                .ephemeral(true),
+        )
+        .await?;
+    let sent = handle.message().await?;
+
+    let Some(ixn) = sent
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(FLOW_TIMEOUT)
+        .await
+    else {
+        handle
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .content("Timed out, nothing was undone")
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    if ixn.data.custom_id != "attendance_undo_confirm" {
+        ixn.create_response(
+            ctx.http(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content("Cancelled, nothing was undone")
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+    ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let log_id = log.id;
+    let removed_ids = removed.iter().map(|row| row.id).collect_vec();
+    let undone_by = ctx.author().id.get() as i64;
+    conn.transaction::<_, (), DbErr>(|txn| {
+        Box::pin(async move {
+            attendance::Entity::delete_many()
+                .filter(attendance::Column::Id.is_in(removed_ids))
+                .exec(txn)
+                .await?;
+
+            let mut log = log.into_active_model();
+            log.undone_at = ActiveValue::Set(Some(Utc::now().naive_utc()));
+            log.undone_by = ActiveValue::Set(Some(undone_by));
+            attendance_log::Entity::update(log).exec(txn).await?;
+            Ok(())
+        })
+    })
+    .await
+    .context("undo attendance log")?;
+
+    let sheet_note = match mark_sheet_rows(ctx.data(), log_id, &removed).await {
+        Ok(0) => String::new(),
+        Ok(marked) => format!(" Marked {marked} attendance sheet rows as undone."),
+        Err(why) => {
+            dbg!(why);
+            String::from(
+                " The attendance sheet could not be updated, so its rows need to be \
+                marked by hand.",
+            )
+        }
+    };
+    let content = format!(
+        "Undid attendance log #{log_id}, removing {} attendance records.{sheet_note}",
+        removed.len()
+    );
+
+    handle
+        .edit(
+            ctx,
+            CreateReply::default().content(content).components(vec![]),
+        )
+        .await?;
+    Ok(())
+}
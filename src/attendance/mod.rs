@@ -1,2 +1,12 @@
 pub(crate) mod attended;
 pub(crate) mod checkin;
+pub(crate) mod command;
+pub(crate) mod event;
+pub(crate) mod leaderboard;
+pub(crate) mod logs;
+pub(crate) mod period;
+pub(crate) mod record;
+pub(crate) mod requirements;
+pub(crate) mod rsvp;
+pub(crate) mod voice;
+pub(crate) mod window;
@@ -0,0 +1,125 @@
+use chrono::{Datelike as _, NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use poise::ChoiceParameter;
+
+use crate::util::time::{from_utc, start_of_day};
+
+#[derive(ChoiceParameter, PartialEq, Eq, Copy, Clone, Debug)]
+pub(crate) enum AttendancePeriod {
+    #[name = "This quarter"]
+    ThisQuarter,
+    #[name = "Last quarter"]
+    LastQuarter,
+    #[name = "This school year"]
+    SchoolYear,
+}
+
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub(crate) enum Season {
+    Fall,
+    Winter,
+    Spring,
+    Summer,
+}
+
+/// An academic quarter: Fall is September through December, Winter is January through March,
+/// Spring is April through June, and Summer is July and August
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub(crate) struct Quarter {
+    pub(crate) season: Season,
+    pub(crate) year: i32,
+}
+
+impl Quarter {
+    pub(crate) fn containing(date: NaiveDate) -> Self {
+        let season = match date.month() {
+            1..=3 => Season::Winter,
+            4..=6 => Season::Spring,
+            7..=8 => Season::Summer,
+            _ => Season::Fall,
+        };
+
+        Self {
+            season,
+            year: date.year(),
+        }
+    }
+
+    pub(crate) fn previous(self) -> Self {
+        let (season, year) = match self.season {
+            Season::Fall => (Season::Summer, self.year),
+            Season::Summer => (Season::Spring, self.year),
+            Season::Spring => (Season::Winter, self.year),
+            Season::Winter => (Season::Fall, self.year - 1),
+        };
+
+        Self { season, year }
+    }
+
+    /// First day of the quarter and first day after it
+    pub(crate) fn dates(self) -> (NaiveDate, NaiveDate) {
+        let (start_month, end_month, end_year) = match self.season {
+            Season::Winter => (1, 4, self.year),
+            Season::Spring => (4, 7, self.year),
+            Season::Summer => (7, 9, self.year),
+            Season::Fall => (9, 1, self.year + 1),
+        };
+
+        (
+            NaiveDate::from_ymd_opt(self.year, start_month, 1).expect("valid quarter start"),
+            NaiveDate::from_ymd_opt(end_year, end_month, 1).expect("valid quarter end"),
+        )
+    }
+
+    pub(crate) fn name(self) -> String {
+        format!("{:?} {}", self.season, self.year)
+    }
+}
+
+impl AttendancePeriod {
+    /// Start (inclusive) and end (exclusive) of the period containing `today`, with a label. The
+    /// bounds are local midnights in `tz`, converted to naive UTC.
+    pub(crate) fn bounds(self, tz: Tz, today: NaiveDate) -> (NaiveDateTime, NaiveDateTime, String) {
+        let (start, end, name) = match self {
+            Self::ThisQuarter | Self::LastQuarter => {
+                let mut quarter = Quarter::containing(today);
+                if self == Self::LastQuarter {
+                    quarter = quarter.previous();
+                }
+                let (start, end) = quarter.dates();
+                (start, end, quarter.name())
+            }
+            Self::SchoolYear => {
+                let start_year = match today.month() {
+                    9.. => today.year(),
+                    _ => today.year() - 1,
+                };
+                (
+                    NaiveDate::from_ymd_opt(start_year, 9, 1).expect("valid year start"),
+                    NaiveDate::from_ymd_opt(start_year + 1, 9, 1).expect("valid year end"),
+                    format!("{start_year}\u{2013}{}", start_year + 1),
+                )
+            }
+        };
+
+        (start_of_day(tz, start), start_of_day(tz, end), name)
+    }
+
+    /// Bounds of the period of the same kind just before this one, for comparisons
+    pub(crate) fn previous_bounds(
+        self,
+        tz: Tz,
+        today: NaiveDate,
+    ) -> (NaiveDateTime, NaiveDateTime, String) {
+        let (start, _, _) = self.bounds(tz, today);
+        let before_start = from_utc(tz, start)
+            .date_naive()
+            .pred_opt()
+            .expect("period starts after the minimum date");
+
+        match self {
+            Self::SchoolYear => Self::SchoolYear.bounds(tz, before_start),
+            Self::ThisQuarter | Self::LastQuarter => Self::ThisQuarter.bounds(tz, before_start),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use anyhow::Context as _;
+use chrono::Utc;
+use entity::attendance;
+use entity::sea_orm_active_enums::AttendanceSource;
+use sea_orm::{ActiveValue, EntityTrait as _};
+use serenity::all::UserId;
+
+use crate::{AppVars, util::roster::check_in_with_email};
+
+/// Records attendance in the database, then mirrors it to the attendance Google Form if one is
+/// configured. Failing to mirror is not an error since the database is the source of truth.
+pub(crate) async fn record_attendance(
+    data: &AppVars,
+    email: &str,
+    discord_id: Option<UserId>,
+    event_name: Option<&str>,
+    source: AttendanceSource,
+    checkin_window_id: Option<i32>,
+) -> anyhow::Result<attendance::Model> {
+    let entry = attendance::ActiveModel {
+        id: ActiveValue::NotSet,
+        email: ActiveValue::Set(email.to_owned()),
+        discord_id: ActiveValue::Set(discord_id.map(|id| id.get() as i64)),
+        event_name: ActiveValue::Set(event_name.map(str::to_owned)),
+        attended_at: ActiveValue::Set(Utc::now().naive_utc()),
+        source: ActiveValue::Set(source),
+        checkin_window_id: ActiveValue::Set(checkin_window_id),
+    };
+
+    let recorded = attendance::Entity::insert(entry)
+        .exec_with_returning(&data.db)
+        .await
+        .context("insert attendance")?;
+
+    if !data.env.attendance_form.id.is_empty()
+        && let Err(why) = check_in_with_email(data, email, event_name).await
+    {
+        dbg!(why);
+    }
+
+    Ok(recorded)
+}
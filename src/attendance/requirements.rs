@@ -0,0 +1,376 @@
+use anyhow::Context as _;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use chrono_tz::Tz;
+use entity::{attendance, attendance_nudge, attendance_requirement};
+use itertools::Itertools as _;
+use poise::CreateReply;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, DatabaseConnection, EntityTrait as _, QueryFilter as _,
+    QueryOrder as _, QuerySelect as _, sea_query::OnConflict,
+};
+use serenity::all::{
+    CacheHttp as _, CreateEmbed, CreateEmbedFooter, CreateMessage, GuildId, UserId,
+};
+use std::collections::HashMap;
+
+use crate::{
+    AppContext, AppError, AppVars,
+    attendance::{leaderboard::committee_choices, period::AttendancePeriod},
+    util::{ContextExtras as _, roster::RosterSheetRow, time},
+};
+
+/// Only nudge members once they are this far into the quarter
+const NUDGE_AFTER_QUARTER_FRACTION: f64 = 0.5;
+const NUDGE_COOLDOWN_DAYS: i64 = 14;
+
+fn applies_to(requirement: &attendance_requirement::Model, member: &RosterSheetRow) -> bool {
+    requirement
+        .committee
+        .as_ref()
+        .is_none_or(|committee| member.committees.contains(committee))
+}
+
+fn counts_toward(requirement: &attendance_requirement::Model, event_name: Option<&str>) -> bool {
+    requirement.event_pattern.as_ref().is_none_or(|pattern| {
+        event_name.is_some_and(|name| name.to_lowercase().contains(&pattern.to_lowercase()))
+    })
+}
+
+/// Event names attended by each email (lowercased) between `start` and `end`. Checking in to the
+/// same event more than once on the same day only counts once.
+async fn events_by_email(
+    conn: &DatabaseConnection,
+    tz: Tz,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> anyhow::Result<HashMap<String, Vec<Option<String>>>> {
+    let rows = attendance::Entity::find()
+        .select_only()
+        .column(attendance::Column::Email)
+        .column(attendance::Column::EventName)
+        .column(attendance::Column::AttendedAt)
+        .filter(attendance::Column::AttendedAt.gte(start))
+        .filter(attendance::Column::AttendedAt.lt(end))
+        .into_tuple::<(String, Option<String>, NaiveDateTime)>()
+        .all(conn)
+        .await
+        .context("fetch quarter attendance")?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(email, event_name, attended_at)| {
+            let event_name = event_name.map(|name| name.to_lowercase());
+            let day = time::from_utc(tz, attended_at).date_naive();
+            (email.to_lowercase(), event_name, day)
+        })
+        .unique()
+        .map(|(email, event_name, _)| (email, event_name))
+        .into_group_map())
+}
+
+/// How many qualifying events a member attended for each requirement that applies to them
+fn progress<'a>(
+    requirements: &'a [attendance_requirement::Model],
+    member: &RosterSheetRow,
+    events: &HashMap<String, Vec<Option<String>>>,
+) -> Vec<(&'a attendance_requirement::Model, i32)> {
+    let attended = events
+        .get(&member.email.to_lowercase())
+        .map_or(&[] as &[_], Vec::as_slice);
+
+    requirements
+        .iter()
+        .filter(|requirement| applies_to(requirement, member))
+        .map(|requirement| {
+            let count = attended
+                .iter()
+                .filter(|event_name| counts_toward(requirement, event_name.as_deref()))
+                .count();
+            (requirement, count as i32)
+        })
+        .collect_vec()
+}
+
+fn progress_lines(progress: &[(&attendance_requirement::Model, i32)]) -> String {
+    progress
+        .iter()
+        .map(|(requirement, count)| {
+            let emoji = match *count >= requirement.min_count {
+                true => "✅",
+                false => "⏳",
+            };
+            format!(
+                "{emoji} **{}**: {count}/{}",
+                requirement.name, requirement.min_count
+            )
+        })
+        .join("\n")
+}
+
+/// See your progress toward this quarter's attendance requirements
+#[poise::command(slash_command)]
+pub(crate) async fn status(ctx: AppContext<'_>) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    let conn = &ctx.data().db;
+    let tz = ctx.data().timezone;
+
+    let Ok(Some(member)) = ctx
+        .data()
+        .roster
+        .write()
+        .await
+        .get_user_from_discord(ctx.author(), false)
+        .await
+    else {
+        ctx.reply_ephemeral(
+            "\
+Cannot find a matching internal member. Double check that your \
+Discord username on the internal roster is correct.",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let requirements = attendance_requirement::Entity::find()
+        .order_by_asc(attendance_requirement::Column::Name)
+        .all(conn)
+        .await
+        .context("fetch requirements")?;
+    let (start, end, quarter_name) = AttendancePeriod::ThisQuarter.bounds(tz, time::today(tz));
+    let events = events_by_email(conn, tz, start, end).await?;
+
+    let progress = progress(&requirements, &member, &events);
+    if progress.is_empty() {
+        ctx.reply_ephemeral("You have no attendance requirements this quarter!")
+            .await?;
+        return Ok(());
+    }
+
+    let embed = CreateEmbed::default()
+        .color(0xff87a6)
+        .title(format!("Attendance Requirements ({quarter_name})"))
+        .description(progress_lines(&progress))
+        .footer(CreateEmbedFooter::new(format!(
+            "Quarter ends {}",
+            time::from_utc(tz, end).format("%B %-d")
+        )));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
+
+#[poise::command(
+    slash_command,
+    subcommands("set_requirement", "list_requirements", "remove_requirement"),
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn requirements(ctx: AppContext<'_>) -> Result<(), AppError> {
+    ctx.reply("base command is a noop").await?;
+    Ok(())
+}
+
+/// Add or update a quarterly attendance requirement
+#[poise::command(slash_command, rename = "set", ephemeral)]
+async fn set_requirement(
+    ctx: AppContext<'_>,
+    #[description = "Name of the requirement, e.g. \"General meetings\""] name: String,
+    #[description = "Minimum number of events per quarter"]
+    #[min = 1]
+    min_count: u32,
+    #[description = "Only count events whose name contains this text; counts every event if empty"]
+    event_pattern: Option<String>,
+    #[description = "Only apply to members of this committee"]
+    #[autocomplete = "committee_choices"]
+    committee: Option<String>,
+) -> Result<(), AppError> {
+    let requirement = attendance_requirement::ActiveModel {
+        id: ActiveValue::NotSet,
+        name: ActiveValue::Set(name.trim().to_owned()),
+        event_pattern: ActiveValue::Set(event_pattern.filter(|pattern| !pattern.trim().is_empty())),
+        min_count: ActiveValue::Set(min_count as i32),
+        committee: ActiveValue::Set(
+            committee.map(|committee| committee.to_lowercase().replace('_', "")),
+        ),
+    };
+
+    attendance_requirement::Entity::insert(requirement)
+        .on_conflict(
+            OnConflict::column(attendance_requirement::Column::Name)
+                .update_columns([
+                    attendance_requirement::Column::EventPattern,
+                    attendance_requirement::Column::MinCount,
+                    attendance_requirement::Column::Committee,
+                ])
+                .to_owned(),
+        )
+        .exec(&ctx.data().db)
+        .await
+        .context("save requirement")?;
+
+    ctx.reply_ephemeral(format!("ok, saved requirement **{}**", name.trim()))
+        .await?;
+    Ok(())
+}
+
+/// List the quarterly attendance requirements
+#[poise::command(slash_command, rename = "list", ephemeral)]
+async fn list_requirements(ctx: AppContext<'_>) -> Result<(), AppError> {
+    let requirements = attendance_requirement::Entity::find()
+        .order_by_asc(attendance_requirement::Column::Name)
+        .all(&ctx.data().db)
+        .await
+        .context("fetch requirements")?;
+
+    if requirements.is_empty() {
+        ctx.reply_ephemeral("No attendance requirements are set")
+            .await?;
+        return Ok(());
+    }
+
+    let lines = requirements
+        .iter()
+        .map(|requirement| {
+            format!(
+                "- **{}**: {} {} per quarter{}",
+                requirement.name,
+                requirement.min_count,
+                requirement
+                    .event_pattern
+                    .as_ref()
+                    .map_or(String::from("events"), |pattern| format!(
+                        "events matching \"{pattern}\""
+                    )),
+                requirement
+                    .committee
+                    .as_ref()
+                    .map_or(String::new(), |committee| format!(" for {committee}")),
+            )
+        })
+        .join("\n");
+
+    ctx.reply_ephemeral(lines).await?;
+    Ok(())
+}
+
+/// Remove a quarterly attendance requirement
+#[poise::command(slash_command, rename = "remove", ephemeral)]
+async fn remove_requirement(
+    ctx: AppContext<'_>,
+    #[description = "Name of the requirement"] name: String,
+) -> Result<(), AppError> {
+    let removed = attendance_requirement::Entity::delete_many()
+        .filter(attendance_requirement::Column::Name.eq(name.trim()))
+        .exec(&ctx.data().db)
+        .await
+        .context("remove requirement")?;
+
+    ctx.reply_ephemeral(match removed.rows_affected {
+        0 => format!("There is no requirement named **{}**", name.trim()),
+        _ => format!("ok, removed requirement **{}**", name.trim()),
+    })
+    .await?;
+    Ok(())
+}
+
+/// DMs members who are behind on this quarter's requirements, at most once per cooldown period
+pub(crate) async fn send_attendance_nudges(
+    ctx: &serenity::all::Context,
+    data: &AppVars,
+) -> anyhow::Result<usize> {
+    let conn = &data.db;
+    let requirements = attendance_requirement::Entity::find()
+        .order_by_asc(attendance_requirement::Column::Name)
+        .all(conn)
+        .await
+        .context("fetch requirements")?;
+    if requirements.is_empty() {
+        return Ok(0);
+    }
+
+    let now = Utc::now().naive_utc();
+    let tz = data.timezone;
+    let (start, end, quarter_name) = AttendancePeriod::ThisQuarter.bounds(tz, time::today(tz));
+    let elapsed = (now - start).num_seconds() as f64 / (end - start).num_seconds() as f64;
+    if elapsed < NUDGE_AFTER_QUARTER_FRACTION {
+        return Ok(0);
+    }
+
+    let roster = data.roster.write().await.snapshot(60).await?;
+    let events = events_by_email(conn, tz, start, end).await?;
+    let recently_nudged = attendance_nudge::Entity::find()
+        .filter(attendance_nudge::Column::LastSentAt.gt(now - TimeDelta::days(NUDGE_COOLDOWN_DAYS)))
+        .all(conn)
+        .await
+        .context("fetch recent nudges")?
+        .into_iter()
+        .map(|nudge| nudge.email.to_lowercase())
+        .collect_vec();
+
+    let guild_id = GuildId::new(data.channels.icssc_guild_id);
+    let user_ids_by_name = ctx
+        .cache
+        .guild(guild_id)
+        .map(|guild| {
+            guild
+                .members
+                .values()
+                .map(|member| (member.user.name.to_lowercase(), member.user.id))
+                .collect::<HashMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    let mut sent = 0;
+    for member in &roster.rows {
+        if recently_nudged.contains(&member.email.to_lowercase()) {
+            continue;
+        }
+
+        let behind = progress(&requirements, member, &events)
+            .into_iter()
+            .filter(|(requirement, count)| *count < requirement.min_count)
+            .collect_vec();
+        if behind.is_empty() {
+            continue;
+        }
+
+        let Some(user_id) = roster.discord_id_of(member).or_else(|| {
+            user_ids_by_name
+                .get(&member.discord.to_lowercase())
+                .copied()
+        }) else {
+            continue;
+        };
+
+        let content = format!(
+            "Hi {}! Just a reminder that you're behind on ICSSC attendance requirements for \
+            {quarter_name}, which ends <t:{}:D>:\n{}\n\n\
+            -# Check your progress any time with `/attendance status`.",
+            member.name,
+            end.and_utc().timestamp(),
+            progress_lines(&behind),
+        );
+        if let Err(why) =
+            UserId::direct_message(user_id, ctx.http(), CreateMessage::new().content(content)).await
+        {
+            dbg!(why);
+            continue;
+        }
+
+        attendance_nudge::Entity::insert(attendance_nudge::ActiveModel {
+            email: ActiveValue::Set(member.email.clone()),
+            last_sent_at: ActiveValue::Set(now),
+        })
+        .on_conflict(
+            OnConflict::column(attendance_nudge::Column::Email)
+                .update_column(attendance_nudge::Column::LastSentAt)
+                .to_owned(),
+        )
+        .exec(conn)
+        .await
+        .context("save nudge")?;
+        sent += 1;
+    }
+
+    Ok(sent)
+}
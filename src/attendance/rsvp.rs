@@ -0,0 +1,243 @@
+use anyhow::Context as _;
+use chrono::TimeDelta;
+use entity::{attendance, checkin_window};
+use itertools::Itertools as _;
+use poise::CreateReply;
+use sea_orm::{
+    ColumnTrait as _, Condition, EntityTrait as _, QueryFilter as _, QuerySelect as _,
+    sea_query::Query,
+};
+use serenity::all::{
+    CreateEmbed, GuildId, Http, Mentionable as _, ScheduledEvent, User, UserPagination,
+};
+use std::collections::HashSet;
+
+use crate::{
+    AppContext, AppError,
+    attendance::{
+        checkin::pick_and_log_attendance,
+        window::{resolve_scheduled_event, synced_event_choices},
+    },
+};
+
+/// Every user marked as interested in a scheduled event
+async fn interested_users(http: &Http, event: &ScheduledEvent) -> anyhow::Result<Vec<User>> {
+    const PAGE_SIZE: u64 = 100;
+
+    let mut users = Vec::new();
+    loop {
+        let after = users
+            .last()
+            .map(|user: &User| UserPagination::After(user.id));
+        let page = http
+            .get_scheduled_event_users(event.guild_id, event.id, Some(PAGE_SIZE), after, None)
+            .await
+            .context("fetch interested users")?;
+
+        let done = (page.len() as u64) < PAGE_SIZE;
+        users.extend(page.into_iter().map(|entry| entry.user));
+        if done {
+            return Ok(users);
+        }
+    }
+}
+
+/// Log attendance starting from the members interested in a synced Discord event
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    guild_only,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn from_event(
+    ctx: AppContext<'_>,
+    #[description = "Synced Discord event to take the interested members from"]
+    #[autocomplete = "synced_event_choices"]
+    scheduled_event: String,
+) -> Result<(), AppError> {
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
+    let event = resolve_scheduled_event(ctx, guild_id, &scheduled_event).await?;
+
+    let interested = interested_users(ctx.http(), &event)
+        .await?
+        .into_iter()
+        .filter(|user| !user.bot)
+        .map(|user| user.id)
+        .collect_vec();
+
+    // pick_and_log_attendance trims this to what the picker holds and notes who was left out
+    let heading = format!(
+        "Logging attendance for **{}** ({} interested)",
+        event.name,
+        interested.len()
+    );
+
+    pick_and_log_attendance(ctx, heading, interested, &event.name, None).await?;
+    Ok(())
+}
+
+/// Attendance rows recorded for a scheduled event, either through a check-in window tied to it or
+/// under the same name within a day of its start
+async fn event_attendee_emails(
+    ctx: AppContext<'_>,
+    guild_id: GuildId,
+    event: &ScheduledEvent,
+) -> anyhow::Result<HashSet<String>> {
+    let start = event.start_time.naive_utc();
+
+    let emails = attendance::Entity::find()
+        .select_only()
+        .column(attendance::Column::Email)
+        .filter(
+            Condition::any()
+                .add(
+                    attendance::Column::CheckinWindowId.in_subquery(
+                        Query::select()
+                            .column(checkin_window::Column::Id)
+                            .from(checkin_window::Entity)
+                            .and_where(checkin_window::Column::GuildId.eq(guild_id.get() as i64))
+                            .and_where(
+                                checkin_window::Column::GuildEventId.eq(event.id.get() as i64),
+                            )
+                            .to_owned(),
+                    ),
+                )
+                .add(
+                    Condition::all()
+                        .add(attendance::Column::EventName.eq(event.name.as_str()))
+                        .add(attendance::Column::AttendedAt.gte(start - TimeDelta::days(1)))
+                        .add(attendance::Column::AttendedAt.lt(start + TimeDelta::days(1))),
+                ),
+        )
+        .into_tuple::<String>()
+        .all(&ctx.data().db)
+        .await
+        .context("fetch event attendance")?;
+
+    Ok(emails
+        .into_iter()
+        .map(|email| email.to_lowercase())
+        .collect())
+}
+
+/// Joins items for an embed field, cutting the list short to fit Discord's field length limit
+fn field_list(items: Vec<String>) -> String {
+    const FIELD_LIMIT: usize = 1024;
+
+    if items.is_empty() {
+        return String::from("None");
+    }
+
+    let total = items.len();
+    let mut joined = String::new();
+    for (shown, item) in items.into_iter().enumerate() {
+        // leave room for the "and N more" suffix
+        if joined.len() + item.len() + 2 > FIELD_LIMIT - 20 {
+            return format!("{joined}, and {} more", total - shown);
+        }
+        if !joined.is_empty() {
+            joined += ", ";
+        }
+        joined += &item;
+    }
+
+    joined
+}
+
+/// Compare who was interested in a synced Discord event with who attended it
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    guild_only,
+    ephemeral,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn rsvp_report(
+    ctx: AppContext<'_>,
+    #[description = "Synced Discord event to report on"]
+    #[autocomplete = "synced_event_choices"]
+    scheduled_event: String,
+) -> Result<(), AppError> {
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
+    ctx.defer_ephemeral().await?;
+
+    let event = resolve_scheduled_event(ctx, guild_id, &scheduled_event).await?;
+    let interested = interested_users(ctx.http(), &event).await?;
+    let attendees = event_attendee_emails(ctx, guild_id, &event).await?;
+    let roster = ctx.data().roster.write().await.snapshot(60).await?;
+
+    let email_of = |user: &User| {
+        roster
+            .find(user.id, &user.name)
+            .map(|row| row.email.to_lowercase())
+    };
+
+    let (interested_rostered, not_rostered): (Vec<_>, Vec<_>) = interested
+        .iter()
+        .filter(|user| !user.bot)
+        .partition(|user| email_of(user).is_some());
+
+    let (showed_up, no_show): (Vec<_>, Vec<_>) = interested_rostered
+        .iter()
+        .partition(|user| email_of(user).is_some_and(|email| attendees.contains(&email)));
+
+    let interested_emails = interested_rostered
+        .iter()
+        .filter_map(|user| email_of(user))
+        .collect::<HashSet<_>>();
+    let walk_ins = roster
+        .rows
+        .iter()
+        .filter(|row| {
+            let email = row.email.to_lowercase();
+            attendees.contains(&email) && !interested_emails.contains(&email)
+        })
+        .map(|row| row.name.as_str())
+        .collect_vec();
+
+    let mentions = |users: &[&&User]| {
+        field_list(
+            users
+                .iter()
+                .map(|user| user.mention().to_string())
+                .collect(),
+        )
+    };
+    let rate = match interested_rostered.len() {
+        0 => String::from("\u{2013}"),
+        n => format!("{:.0}%", 100.0 * showed_up.len() as f64 / n as f64),
+    };
+
+    let embed = CreateEmbed::default()
+        .color(0xff87a6)
+        .title(format!("RSVPs vs. Attendance: {}", event.name))
+        .description(format!(
+            "{} interested, {} attended, {rate} of interested internal members showed up",
+            interested.len(),
+            attendees.len(),
+        ))
+        .field(
+            format!("Interested and attended ({})", showed_up.len()),
+            mentions(&showed_up),
+            false,
+        )
+        .field(
+            format!("Interested but absent ({})", no_show.len()),
+            mentions(&no_show),
+            false,
+        )
+        .field(
+            format!("Attended without RSVP ({})", walk_ins.len()),
+            field_list(walk_ins.iter().map(|&name| name.to_owned()).collect()),
+            false,
+        )
+        .field(
+            format!("Interested, not on roster ({})", not_rostered.len()),
+            mentions(&not_rostered.iter().collect_vec()),
+            false,
+        );
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+    Ok(())
+}
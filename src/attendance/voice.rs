@@ -0,0 +1,74 @@
+use anyhow::{Context as _, bail};
+use entity::sea_orm_active_enums::AttendanceSource;
+use itertools::Itertools as _;
+use serenity::all::{ChannelType, GuildChannel};
+
+use crate::{
+    AppContext, AppError,
+    attendance::checkin::{LogOrigin, confirm_attendance_log},
+    util::ContextExtras as _,
+};
+
+/// Record attendance for everyone currently in a voice or stage channel
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    guild_only,
+    ephemeral,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn log_voice(
+    ctx: AppContext<'_>,
+    #[description = "Voice or stage channel the meeting is in"]
+    #[channel_types("Voice", "Stage")]
+    channel: GuildChannel,
+    #[description = "Name of the event"] event_name: String,
+) -> Result<(), AppError> {
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
+    if !matches!(channel.kind, ChannelType::Voice | ChannelType::Stage) {
+        bail!("{} is not a voice or stage channel", channel.name);
+    }
+
+    ctx.defer_ephemeral().await?;
+
+    let user_ids = {
+        let guild = ctx.guild().context("guild is not cached")?;
+        guild
+            .voice_states
+            .values()
+            .filter(|state| state.channel_id == Some(channel.id))
+            .map(|state| state.user_id)
+            .filter(|user_id| {
+                guild
+                    .members
+                    .get(user_id)
+                    .is_none_or(|member| !member.user.bot)
+            })
+            .collect_vec()
+    };
+
+    if user_ids.is_empty() {
+        ctx.reply_ephemeral(format!("Nobody is in {} right now", channel.name))
+            .await?;
+        return Ok(());
+    }
+
+    let origin = LogOrigin {
+        guild_id,
+        logged_by: ctx.author().id,
+        message: None,
+    };
+    let response = confirm_attendance_log(
+        ctx.serenity_context(),
+        ctx.data(),
+        &origin,
+        &user_ids,
+        Some(event_name.trim()),
+        AttendanceSource::Bulk,
+        true,
+    )
+    .await?;
+
+    ctx.reply_ephemeral(response).await?;
+    Ok(())
+}
@@ -0,0 +1,387 @@
+use anyhow::{Context as _, bail};
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use entity::{checkin_window, server_event};
+use hmac::{Hmac, Mac as _};
+use itertools::Itertools as _;
+use rand::seq::IndexedRandom as _;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, DatabaseConnection, EntityTrait as _, QueryFilter as _,
+    QueryOrder as _, sea_query::Expr,
+};
+use serenity::all::{
+    AutocompleteChoice, ChannelId, CreateMessage, EditMessage, GuildId, Http, MessageId,
+    ScheduledEvent, ScheduledEventId,
+};
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use crate::{AppContext, AppError, util::ContextExtras as _};
+
+/// Letters and digits that are hard to mix up when read off a projector
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 6;
+const ROTATION_PERIOD_SECS: i64 = 30;
+
+pub(crate) fn random_code() -> String {
+    let mut rng = rand::rng();
+    (0..CODE_LENGTH)
+        .map(|_| {
+            char::from(
+                *CODE_ALPHABET
+                    .choose(&mut rng)
+                    .expect("alphabet is not empty"),
+            )
+        })
+        .collect()
+}
+
+/// HMAC-SHA256 of the step keyed by the window's secret, one byte per character. The alphabet has
+/// 32 characters, so every character is equally likely.
+fn rotating_code(secret: &str, step: i64) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(&step.to_be_bytes());
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .take(CODE_LENGTH)
+        .map(|&byte| char::from(CODE_ALPHABET[usize::from(byte) % CODE_ALPHABET.len()]))
+        .collect()
+}
+
+fn rotation_step(at: NaiveDateTime) -> i64 {
+    at.and_utc().timestamp().div_euclid(ROTATION_PERIOD_SECS)
+}
+
+/// The code members should currently enter for a window
+pub(crate) fn current_code(window: &checkin_window::Model, now: NaiveDateTime) -> String {
+    match window.rotating {
+        true => rotating_code(&window.code, rotation_step(now)),
+        false => window.code.clone(),
+    }
+}
+
+fn code_matches(window: &checkin_window::Model, code: &str, now: NaiveDateTime) -> bool {
+    let code = code.trim().to_uppercase();
+    match window.rotating {
+        // also accept the previous code, in case it rotated while someone was typing
+        true => [0, 1]
+            .into_iter()
+            .any(|back| rotating_code(&window.code, rotation_step(now) - back) == code),
+        false => window.code == code,
+    }
+}
+
+/// Windows open at `now`, in `guild_id` or in every guild if it's `None`
+pub(crate) async fn open_windows(
+    conn: &DatabaseConnection,
+    guild_id: Option<GuildId>,
+    now: NaiveDateTime,
+) -> anyhow::Result<Vec<checkin_window::Model>> {
+    let mut query = checkin_window::Entity::find();
+    if let Some(guild_id) = guild_id {
+        query = query.filter(checkin_window::Column::GuildId.eq(guild_id.get() as i64));
+    }
+
+    query
+        .filter(checkin_window::Column::OpensAt.lte(now))
+        .filter(checkin_window::Column::ClosesAt.gt(now))
+        .order_by_desc(checkin_window::Column::OpensAt)
+        .all(conn)
+        .await
+        .context("fetch open check-in windows")
+}
+
+/// Finds the open window accepting `code` in any guild, since codes can be entered in DMs
+pub(crate) async fn find_window_for_code(
+    conn: &DatabaseConnection,
+    code: &str,
+) -> anyhow::Result<Option<checkin_window::Model>> {
+    let now = Utc::now().naive_utc();
+    Ok(open_windows(conn, None, now)
+        .await?
+        .into_iter()
+        .find(|window| code_matches(window, code, now)))
+}
+
+fn code_message(window: &checkin_window::Model, now: NaiveDateTime) -> String {
+    if now >= window.closes_at {
+        return format!("Check-in for **{}** has closed.", window.event_name);
+    }
+
+    format!(
+        "# {}\nCheck in to **{}** with `/checkin code:{0}` until <t:{}:t>.",
+        current_code(window, now),
+        window.event_name,
+        window.closes_at.and_utc().timestamp(),
+    )
+}
+
+/// Windows whose posted code is being kept up to date, so resuming after a reconnect doesn't
+/// start a second task for the same message
+static UPDATING_WINDOWS: LazyLock<Mutex<HashSet<i32>>> = LazyLock::new(Mutex::default);
+
+/// Keeps the posted code in sync with the window until it closes
+async fn rotate_code_message(
+    http: Arc<Http>,
+    conn: DatabaseConnection,
+    window_id: i32,
+    channel_id: ChannelId,
+    message_id: MessageId,
+) -> anyhow::Result<()> {
+    if !UPDATING_WINDOWS
+        .lock()
+        .expect("lock not poisoned")
+        .insert(window_id)
+    {
+        return Ok(());
+    }
+
+    let result =
+        update_code_message_until_closed(&http, &conn, window_id, channel_id, message_id).await;
+    UPDATING_WINDOWS
+        .lock()
+        .expect("lock not poisoned")
+        .remove(&window_id);
+    result
+}
+
+async fn update_code_message_until_closed(
+    http: &Http,
+    conn: &DatabaseConnection,
+    window_id: i32,
+    channel_id: ChannelId,
+    message_id: MessageId,
+) -> anyhow::Result<()> {
+    loop {
+        let Some(window) = checkin_window::Entity::find_by_id(window_id)
+            .one(conn)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        let now = Utc::now().naive_utc();
+        channel_id
+            .edit_message(
+                http,
+                message_id,
+                EditMessage::new().content(code_message(&window, now)),
+            )
+            .await?;
+        if now >= window.closes_at {
+            return Ok(());
+        }
+
+        let until_next = match window.rotating {
+            true => ROTATION_PERIOD_SECS - now.and_utc().timestamp() % ROTATION_PERIOD_SECS,
+            false => (window.closes_at - now).num_seconds() + 1,
+        };
+        tokio::time::sleep(Duration::from_secs(until_next.clamp(1, 60) as u64)).await;
+    }
+}
+
+/// Picks up updating the posted code of every open window, e.g. after the bot restarts
+pub(crate) async fn resume_code_messages(
+    http: Arc<Http>,
+    conn: DatabaseConnection,
+) -> anyhow::Result<usize> {
+    let windows = open_windows(&conn, None, Utc::now().naive_utc()).await?;
+    let mut resumed = 0;
+
+    for window in windows {
+        let (Some(channel_id), Some(message_id)) = (window.code_channel_id, window.code_message_id)
+        else {
+            continue;
+        };
+
+        tokio::spawn(rotate_code_message(
+            http.clone(),
+            conn.clone(),
+            window.id,
+            ChannelId::new(channel_id as u64),
+            MessageId::new(message_id as u64),
+        ));
+        resumed += 1;
+    }
+
+    Ok(resumed)
+}
+
+pub(crate) async fn synced_event_choices(
+    ctx: AppContext<'_>,
+    partial: &str,
+) -> Vec<AutocompleteChoice> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return vec![];
+    };
+
+    let Ok(synced) = server_event::Entity::find()
+        .filter(server_event::Column::GuildId.eq(guild_id.get() as i64))
+        .all(&ctx.data().db)
+        .await
+    else {
+        return vec![];
+    };
+    let Ok(events) = guild_id.scheduled_events(ctx.http(), false).await else {
+        return vec![];
+    };
+
+    let partial = partial.to_lowercase();
+    events
+        .into_iter()
+        .filter(|event| {
+            synced
+                .iter()
+                .any(|row| row.guild_event_id == event.id.get() as i64)
+        })
+        .filter(|event| event.name.to_lowercase().contains(&partial))
+        .sorted_by_key(|event| event.start_time)
+        .take(25)
+        .map(|event| AutocompleteChoice::new(event.name, event.id.to_string()))
+        .collect_vec()
+}
+
+/// Looks up a scheduled event picked from [`synced_event_choices`]
+pub(crate) async fn resolve_scheduled_event(
+    ctx: AppContext<'_>,
+    guild_id: GuildId,
+    id: &str,
+) -> anyhow::Result<ScheduledEvent> {
+    let Ok(id) = id.parse::<u64>() else {
+        bail!("Please choose one of the suggested scheduled events");
+    };
+
+    ctx.http()
+        .get_scheduled_event(guild_id, ScheduledEventId::new(id), false)
+        .await
+        .context("That scheduled event doesn't exist")
+}
+
+/// Open a check-in window for an event and post its code in this channel
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    guild_only,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn open_checkin(
+    ctx: AppContext<'_>,
+    #[description = "Name of the event; defaults to the name of the scheduled event"]
+    event_name: Option<String>,
+    #[description = "Synced Discord event this check-in is for"]
+    #[autocomplete = "synced_event_choices"]
+    scheduled_event: Option<String>,
+    #[description = "How long check-in stays open, in minutes (default 60)"]
+    #[min = 1]
+    #[max = 1440]
+    minutes: Option<u32>,
+    #[description = "Change the code every 30 seconds so it has to be read at the event"]
+    rotating: Option<bool>,
+) -> Result<(), AppError> {
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
+    let conn = &ctx.data().db;
+
+    let scheduled_event = match scheduled_event {
+        None => None,
+        Some(id) => Some(resolve_scheduled_event(ctx, guild_id, &id).await?),
+    };
+
+    let Some(event_name) = event_name
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| scheduled_event.as_ref().map(|event| event.name.clone()))
+    else {
+        bail!("Please give an event name or choose a scheduled event");
+    };
+
+    let rotating = rotating.unwrap_or(false);
+    let now = Utc::now().naive_utc();
+    let window = checkin_window::ActiveModel {
+        id: ActiveValue::NotSet,
+        guild_id: ActiveValue::Set(guild_id.get() as i64),
+        event_name: ActiveValue::Set(event_name),
+        // rotating windows store a longer secret that is never shown
+        code: ActiveValue::Set(match rotating {
+            true => random_code() + &random_code(),
+            false => random_code(),
+        }),
+        rotating: ActiveValue::Set(rotating),
+        guild_event_id: ActiveValue::Set(scheduled_event.map(|event| event.id.get() as i64)),
+        opened_by: ActiveValue::Set(ctx.author().id.get() as i64),
+        opens_at: ActiveValue::Set(now),
+        closes_at: ActiveValue::Set(now + TimeDelta::minutes(i64::from(minutes.unwrap_or(60)))),
+        code_channel_id: ActiveValue::NotSet,
+        code_message_id: ActiveValue::NotSet,
+    };
+    let window = checkin_window::Entity::insert(window)
+        .exec_with_returning(conn)
+        .await
+        .context("open check-in window")?;
+
+    let posted = ctx
+        .channel_id()
+        .send_message(
+            ctx.http(),
+            CreateMessage::new().content(code_message(&window, now)),
+        )
+        .await?;
+
+    checkin_window::Entity::update_many()
+        .col_expr(
+            checkin_window::Column::CodeChannelId,
+            Expr::value(posted.channel_id.get() as i64),
+        )
+        .col_expr(
+            checkin_window::Column::CodeMessageId,
+            Expr::value(posted.id.get() as i64),
+        )
+        .filter(checkin_window::Column::Id.eq(window.id))
+        .exec(conn)
+        .await
+        .context("save check-in code message")?;
+
+    tokio::spawn(rotate_code_message(
+        ctx.serenity_context().http.clone(),
+        conn.clone(),
+        window.id,
+        posted.channel_id,
+        posted.id,
+    ));
+
+    ctx.reply_ephemeral(format!(
+        "Opened check-in for **{}** until <t:{}:t>",
+        window.event_name,
+        window.closes_at.and_utc().timestamp()
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Close every open check-in window in this server
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    guild_only,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn close_checkin(ctx: AppContext<'_>) -> Result<(), AppError> {
+    let guild_id: GuildId = ctx.guild_id().context("command is guild_only")?;
+    let now = Utc::now().naive_utc();
+
+    let closed = checkin_window::Entity::update_many()
+        .col_expr(checkin_window::Column::ClosesAt, Expr::value(now))
+        .filter(checkin_window::Column::GuildId.eq(guild_id.get() as i64))
+        .filter(checkin_window::Column::ClosesAt.gt(now))
+        .exec(&ctx.data().db)
+        .await
+        .context("close check-in windows")?;
+
+    ctx.reply_ephemeral(format!("Closed {} check-in windows", closed.rows_affected))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,613 @@
+// Assign bits and bytes to new fams with the matchy pairing engine
+
+use anyhow::{Context as _, bail, ensure};
+use chrono::Utc;
+use entity::{bnb_fam, bnb_fam_member, bnb_signup, sea_orm_active_enums::BnbFamRole};
+use itertools::Itertools as _;
+use poise::CreateReply;
+use rand::seq::SliceRandom as _;
+use rand_chacha::rand_core::SeedableRng as _;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, DatabaseConnection, DbErr, EntityTrait as _, QueryFilter as _,
+    QueryOrder as _, TransactionTrait as _, sea_query::OnConflict,
+};
+use serenity::all::{
+    ButtonStyle, ChannelType, ComponentInteraction, CreateActionRow, CreateButton, CreateChannel,
+    CreateEmbed, CreateInputText, CreateInteractionResponse, CreateInteractionResponseMessage,
+    CreateMessage, CreateQuickModal, EditRole, GuildId, InputTextStyle, Mentionable,
+    PermissionOverwrite, PermissionOverwriteType, Permissions, UserId,
+};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    AppContext, AppError, AppVars,
+    bitsnbytes::fam::guild_usernames,
+    matchy::{
+        helpers::{Match, Pairing, checksum_matching, hash_seed},
+        matching::graph_pair_by,
+    },
+    util::{
+        ContextExtras as _,
+        components::{FLOW_TIMEOUT, confirm_buttons_row},
+    },
+};
+
+/// Splits a comma or newline separated list from the signup modal
+fn split_list(text: &str) -> impl Iterator<Item = &str> {
+    text.split([',', '\n'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+pub(crate) struct BnbSignup<'a> {
+    ctx: &'a serenity::all::Context,
+    data: &'a AppVars,
+}
+
+impl<'a> BnbSignup<'a> {
+    pub(crate) fn new(ctx: &'a serenity::all::Context, data: &'a AppVars) -> Self {
+        Self { ctx, data }
+    }
+
+    /// Opens the signup modal, prefilled with any previous answers, and saves the response
+    pub(crate) async fn open(
+        &self,
+        interaction: &ComponentInteraction,
+        role: BnbFamRole,
+    ) -> anyhow::Result<()> {
+        let discord_id = interaction.user.id.get() as i64;
+        let previous = bnb_signup::Entity::find_by_id(discord_id)
+            .one(&self.data.db)
+            .await
+            .context("fetch bnb signup")?;
+        let (interests, preferences) = previous
+            .map(|signup| (signup.interests, signup.preferences))
+            .unwrap_or_default();
+
+        let (title, preference_label) = match role {
+            BnbFamRole::Bit => ("Sign up as a Bit", "Bytes you'd like (usernames, optional)"),
+            BnbFamRole::Byte => (
+                "Sign up as a Byte",
+                "Co-bytes or bits you'd like (optional)",
+            ),
+        };
+        let Some(modal) = interaction
+            .quick_modal(
+                self.ctx,
+                CreateQuickModal::new(title)
+                    .field(
+                        CreateInputText::new(
+                            InputTextStyle::Paragraph,
+                            "Interests (comma separated)",
+                            "interests",
+                        )
+                        .placeholder("e.g. web dev, hiking, boba")
+                        .value(interests)
+                        .required(true),
+                    )
+                    .field(
+                        CreateInputText::new(
+                            InputTextStyle::Paragraph,
+                            preference_label,
+                            "preferences",
+                        )
+                        .value(preferences)
+                        .required(false),
+                    )
+                    .timeout(FLOW_TIMEOUT),
+            )
+            .await?
+        else {
+            return Ok(());
+        };
+
+        let mut inputs = modal.inputs.into_iter();
+        let signup = bnb_signup::ActiveModel {
+            discord_id: ActiveValue::Set(discord_id),
+            role: ActiveValue::Set(role.clone()),
+            interests: ActiveValue::Set(split_list(&inputs.next().unwrap_or_default()).join(", ")),
+            preferences: ActiveValue::Set(
+                split_list(&inputs.next().unwrap_or_default()).join(", "),
+            ),
+            updated_at: ActiveValue::Set(Utc::now().naive_utc()),
+        };
+        bnb_signup::Entity::insert(signup)
+            .on_conflict(
+                OnConflict::column(bnb_signup::Column::DiscordId)
+                    .update_columns([
+                        bnb_signup::Column::Role,
+                        bnb_signup::Column::Interests,
+                        bnb_signup::Column::Preferences,
+                        bnb_signup::Column::UpdatedAt,
+                    ])
+                    .to_owned(),
+            )
+            .exec(&self.data.db)
+            .await
+            .context("save bnb signup")?;
+
+        let role_name = match role {
+            BnbFamRole::Bit => "bit",
+            BnbFamRole::Byte => "byte",
+        };
+        modal
+            .interaction
+            .create_response(
+                self.ctx,
+                CreateInteractionResponse::Message(
+                    CreateInteractionResponseMessage::new()
+                        .content(format!(
+                            "Saved your Bits & Bytes signup as a {role_name}. Click the button \
+                            again to change your answers."
+                        ))
+                        .ephemeral(true),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Post the Bits & Bytes signup buttons in this channel
+#[poise::command(slash_command, rename = "signup_message", guild_only, ephemeral)]
+pub(crate) async fn signup_message(ctx: AppContext<'_>) -> Result<(), AppError> {
+    ctx.channel_id()
+        .send_message(
+            ctx.http(),
+            CreateMessage::new()
+                .content(
+                    "**Bits & Bytes signups are open!** Tell us what you're into so we can put \
+                    you in a fam you'll love.",
+                )
+                .components(vec![CreateActionRow::Buttons(vec![
+                    CreateButton::new("bnb_signup_bit")
+                        .label("Sign up as a Bit")
+                        .style(ButtonStyle::Primary),
+                    CreateButton::new("bnb_signup_byte")
+                        .label("Sign up as a Byte")
+                        .style(ButtonStyle::Secondary),
+                ])]),
+        )
+        .await?;
+
+    ctx.reply_ephemeral("Posted the signup buttons").await?;
+    Ok(())
+}
+
+struct Candidate {
+    interests: HashSet<String>,
+    preferences: HashSet<UserId>,
+}
+
+/// Signed up bits and bytes, each sorted by user ID so proposals only depend on the seed
+async fn candidates(
+    ctx: AppContext<'_>,
+    guild_id: GuildId,
+) -> anyhow::Result<(Vec<UserId>, Vec<UserId>, HashMap<UserId, Candidate>)> {
+    let signups = bnb_signup::Entity::find()
+        .order_by_asc(bnb_signup::Column::DiscordId)
+        .all(&ctx.data().db)
+        .await
+        .context("fetch bnb signups")?;
+    let usernames = guild_usernames(ctx.serenity_context(), guild_id).await?;
+
+    let mut bits = Vec::new();
+    let mut bytes = Vec::new();
+    let mut candidates = HashMap::new();
+    for signup in signups {
+        let user_id = UserId::new(signup.discord_id as u64);
+        let preferences = split_list(&signup.preferences)
+            .filter_map(|preference| {
+                let preference = preference
+                    .trim_start_matches("<@")
+                    .trim_end_matches('>')
+                    .trim_start_matches('@')
+                    .to_lowercase();
+                preference
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|&id| id != 0)
+                    .map(UserId::new)
+                    .or_else(|| usernames.get(&preference).copied())
+            })
+            .collect();
+        let candidate = Candidate {
+            interests: split_list(&signup.interests)
+                .map(str::to_lowercase)
+                .collect(),
+            preferences,
+        };
+
+        match signup.role {
+            BnbFamRole::Bit => bits.push(user_id),
+            BnbFamRole::Byte => bytes.push(user_id),
+        }
+        candidates.insert(user_id, candidate);
+    }
+
+    Ok((bits, bytes, candidates))
+}
+
+/// Bytes who already led a fam together, so they get new co-bytes
+async fn previous_byte_groups(conn: &DatabaseConnection) -> anyhow::Result<Vec<Match<UserId>>> {
+    let fams = bnb_fam::Entity::find()
+        .find_with_related(bnb_fam_member::Entity)
+        .all(conn)
+        .await
+        .context("fetch fams")?;
+
+    Ok(fams
+        .into_iter()
+        .map(|(_, members)| {
+            members
+                .into_iter()
+                .filter(|member| member.role == BnbFamRole::Byte)
+                .map(|member| UserId::new(member.discord_id as u64))
+                .collect_vec()
+        })
+        .collect_vec())
+}
+
+struct ProposedFam {
+    bytes: Vec<UserId>,
+    bits: Vec<UserId>,
+}
+
+/// How well a bit fits with a byte: liking each other counts for more than a shared interest
+fn affinity(bit: (UserId, &Candidate), byte: (UserId, &Candidate)) -> usize {
+    let preferred = usize::from(bit.1.preferences.contains(&byte.0))
+        + usize::from(byte.1.preferences.contains(&bit.0));
+    3 * preferred + bit.1.interests.intersection(&byte.1.interests).count()
+}
+
+/// Groups bytes into fam leads with `graph_pair_by`, avoiding bytes who led a fam together before
+/// or have nothing in common, then spreads the bits evenly across fams by affinity. Returns the
+/// fams and the bytes who had to be grouped with a co-byte they were meant to avoid.
+fn propose(
+    bits: Vec<UserId>,
+    bytes: Vec<UserId>,
+    candidates: &HashMap<UserId, Candidate>,
+    previous: &[Match<UserId>],
+    seed: u64,
+) -> anyhow::Result<(Vec<ProposedFam>, Vec<UserId>)> {
+    ensure!(
+        bytes.len() >= 2,
+        "Need at least two bytes signed up to make fams (found {}).",
+        bytes.len()
+    );
+
+    let Pairing(byte_groups, imperfect) = graph_pair_by(bytes, previous, seed, |a, b| {
+        let (a_id, b_id) = (*a, *b);
+        let (a, b) = (&candidates[&a_id], &candidates[&b_id]);
+        a.preferences.contains(&b_id)
+            || b.preferences.contains(&a_id)
+            || !a.interests.is_disjoint(&b.interests)
+    })?;
+
+    let mut fams = byte_groups
+        .into_iter()
+        .map(|bytes| ProposedFam {
+            bytes,
+            bits: Vec::new(),
+        })
+        .collect_vec();
+
+    let mut bits = bits;
+    bits.shuffle(&mut rand_chacha::ChaChaRng::seed_from_u64(seed));
+    let capacity = bits.len().div_ceil(fams.len());
+    for bit in bits {
+        let score = |fam: &ProposedFam| {
+            fam.bytes
+                .iter()
+                .map(|byte| affinity((bit, &candidates[&bit]), (*byte, &candidates[byte])))
+                .sum::<usize>()
+        };
+        let (_, fam) = fams
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, fam)| fam.bits.len() < capacity)
+            .max_by_key(|(i, fam)| {
+                (
+                    score(fam),
+                    std::cmp::Reverse(fam.bits.len()),
+                    std::cmp::Reverse(*i),
+                )
+            })
+            .context("every fam is unexpectedly full")?;
+        fam.bits.push(bit);
+    }
+
+    Ok((fams, imperfect))
+}
+
+fn fam_members(fam: &ProposedFam) -> Match<UserId> {
+    fam.bytes.iter().chain(&fam.bits).copied().collect_vec()
+}
+
+/// Names like "Fam 3" that aren't taken by an existing fam
+async fn free_fam_names(conn: &DatabaseConnection, count: usize) -> anyhow::Result<Vec<String>> {
+    let taken = bnb_fam::Entity::find()
+        .all(conn)
+        .await
+        .context("fetch fams")?
+        .into_iter()
+        .map(|fam| fam.name.to_lowercase())
+        .collect::<HashSet<_>>();
+
+    Ok((1..)
+        .map(|n| format!("Fam {n}"))
+        .filter(|name| !taken.contains(&name.to_lowercase()))
+        .take(count)
+        .collect_vec())
+}
+
+/// Creates the fam's role and private channel, saves the fam in place of its members' signups,
+/// and gives the role to its members.
+/// Anything created in Discord is deleted again if a later step fails.
+async fn create_fam(
+    ctx: AppContext<'_>,
+    guild_id: GuildId,
+    name: String,
+    fam: &ProposedFam,
+) -> anyhow::Result<()> {
+    let role = guild_id
+        .create_role(ctx.http(), EditRole::new().name(&name).mentionable(true))
+        .await
+        .context("create fam role")?;
+    let channel = guild_id
+        .create_channel(
+            ctx.http(),
+            CreateChannel::new(name.to_lowercase().replace(' ', "-"))
+                .kind(ChannelType::Text)
+                .permissions(vec![
+                    PermissionOverwrite {
+                        allow: Permissions::empty(),
+                        deny: Permissions::VIEW_CHANNEL,
+                        kind: PermissionOverwriteType::Role(guild_id.everyone_role()),
+                    },
+                    PermissionOverwrite {
+                        allow: Permissions::VIEW_CHANNEL,
+                        deny: Permissions::empty(),
+                        kind: PermissionOverwriteType::Role(role.id),
+                    },
+                ]),
+        )
+        .await;
+    let channel = match channel {
+        Ok(channel) => channel,
+        Err(why) => {
+            if let Err(why) = guild_id.delete_role(ctx.http(), role.id).await {
+                dbg!(why);
+            }
+            return Err(why).context("create fam channel");
+        }
+    };
+
+    let members = fam
+        .bytes
+        .iter()
+        .map(|id| (*id, BnbFamRole::Byte))
+        .chain(fam.bits.iter().map(|id| (*id, BnbFamRole::Bit)))
+        .collect_vec();
+    let member_ids = members
+        .iter()
+        .map(|(user_id, _)| user_id.get() as i64)
+        .collect_vec();
+    let role_id = role.id.get() as i64;
+    let channel_id = channel.id.get() as i64;
+    let saved = ctx
+        .data()
+        .db
+        .transaction::<_, (), DbErr>(|txn| {
+            Box::pin(async move {
+                let fam = bnb_fam::Entity::insert(bnb_fam::ActiveModel {
+                    name: ActiveValue::Set(name),
+                    role_id: ActiveValue::Set(Some(role_id)),
+                    channel_id: ActiveValue::Set(Some(channel_id)),
+                    ..Default::default()
+                })
+                .exec_with_returning(txn)
+                .await?;
+                bnb_fam_member::Entity::insert_many(members.into_iter().map(|(user_id, role)| {
+                    bnb_fam_member::ActiveModel {
+                        fam_id: ActiveValue::Set(fam.id),
+                        discord_id: ActiveValue::Set(user_id.get() as i64),
+                        role: ActiveValue::Set(role),
+                    }
+                }))
+                .exec(txn)
+                .await?;
+                // assigned signups are used up, so they aren't proposed again next time
+                bnb_signup::Entity::delete_many()
+                    .filter(bnb_signup::Column::DiscordId.is_in(member_ids))
+                    .exec(txn)
+                    .await?;
+                Ok(())
+            })
+        })
+        .await;
+    if let Err(why) = saved {
+        if let Err(why) = channel.delete(ctx.http()).await {
+            dbg!(why);
+        }
+        if let Err(why) = guild_id.delete_role(ctx.http(), role.id).await {
+            dbg!(why);
+        }
+        return Err(why).context("save assigned fam");
+    }
+
+    for user_id in fam_members(fam) {
+        if let Err(why) = ctx
+            .http()
+            .add_member_role(
+                guild_id,
+                user_id,
+                role.id,
+                Some("Bits & Bytes fam assignment"),
+            )
+            .await
+        {
+            dbg!(why);
+        }
+    }
+
+    Ok(())
+}
+
+/// Propose fams for everyone who signed up, then create their roles and channels on confirmation
+#[poise::command(slash_command, rename = "assign", guild_only, ephemeral)]
+pub(crate) async fn assign(
+    ctx: AppContext<'_>,
+    #[description = "A seed for the assignment (e.g. the date), or a key from a previous run"]
+    seed: String,
+) -> Result<(), AppError> {
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
+    ctx.defer_ephemeral().await?;
+    let conn = &ctx.data().db;
+
+    // a key is a seed with the proposal's checksum, so rerunning it checks nothing changed
+    let (seed_str, expected_checksum) = match seed.rsplit_once('_') {
+        Some((seed_str, checksum))
+            if checksum.len() == 8 && checksum.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            (seed_str.to_owned(), Some(checksum.to_owned()))
+        }
+        _ => (seed, None),
+    };
+    let seed = hash_seed(&seed_str);
+
+    let (bits, bytes, candidates) = candidates(ctx, guild_id).await?;
+    let (bit_count, byte_count) = (bits.len(), bytes.len());
+    let previous = previous_byte_groups(conn).await?;
+    let (fams, imperfect) = propose(bits, bytes, &candidates, &previous, seed)?;
+
+    let checksum = checksum_matching(seed, &fams.iter().map(fam_members).collect_vec());
+    if let Some(expected) = expected_checksum
+        && expected != checksum
+    {
+        bail!(
+            "Key mismatch. This can happen if you typed the key incorrectly, or signups have \
+            changed since this key was generated. Run `/bnb manage assign` with a seed again to \
+            get a new key."
+        );
+    }
+
+    let names = free_fam_names(conn, fams.len()).await?;
+    let lines = names
+        .iter()
+        .zip(&fams)
+        .map(|(name, fam)| {
+            format!(
+                "**{name}**: bytes {}; bits {}",
+                fam.bytes.iter().map(Mentionable::mention).join(", "),
+                match fam.bits.is_empty() {
+                    true => String::from("\u{2013}"),
+                    false => fam.bits.iter().map(Mentionable::mention).join(", "),
+                }
+            )
+        })
+        .collect_vec();
+    // embeds hold 4096 characters each
+    let embeds = lines
+        .into_iter()
+        .fold(Vec::<String>::new(), |mut chunks, line| {
+            match chunks.last_mut() {
+                Some(chunk) if chunk.len() + line.len() < 4000 => {
+                    chunk.push('\n');
+                    chunk.push_str(&line);
+                }
+                _ => chunks.push(line),
+            }
+            chunks
+        })
+        .into_iter()
+        .map(|chunk| CreateEmbed::default().color(0xff87a6).description(chunk))
+        .collect_vec();
+
+    let imperfect_note = match imperfect.is_empty() {
+        true => String::new(),
+        false => format!(
+            "\nThese bytes could only be grouped with a co-byte they led a fam with before or \
+            share no interests with: {}",
+            imperfect.iter().map(Mentionable::mention).join(", ")
+        ),
+    };
+    let content = format!(
+        "Proposed {} fams for {byte_count} bytes and {bit_count} bits.{imperfect_note}\n\
+        To get this exact proposal again, use this key: `{seed_str}_{checksum}`\n\
+        Confirm to create a role and private channel for each fam.",
+        fams.len()
+    );
+
+    let mut reply = CreateReply::default()
+        .content(content)
+        .components(vec![confirm_buttons_row("bnb_assign")])
+        .ephemeral(true);
+    reply.embeds = embeds;
+    let handle = ctx.send(reply).await?;
+    let sent = handle.message().await?;
+
+    let Some(ixn) = sent
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(FLOW_TIMEOUT)
+        .await
+    else {
+        handle
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .content("Timed out, no fams were created")
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    if ixn.data.custom_id != "bnb_assign_confirm" {
+        ixn.create_response(
+            ctx.http(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content("Cancelled, no fams were created")
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+    ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let mut created = 0;
+    let mut failed = Vec::new();
+    for (name, fam) in names.into_iter().zip(&fams) {
+        match create_fam(ctx, guild_id, name.clone(), fam).await {
+            Ok(()) => created += 1,
+            Err(why) => {
+                dbg!(why);
+                failed.push(name);
+            }
+        }
+    }
+
+    let failed_note = match failed.is_empty() {
+        true => String::new(),
+        false => format!(
+            "\nCould not create {}; create them with `/bnb manage create` instead.",
+            failed.join(", ")
+        ),
+    };
+    handle
+        .edit(
+            ctx,
+            CreateReply::default()
+                .content(format!(
+                    "Created {created} fams with their roles and channels. Rename them with \
+                    `/bnb manage rename`.{failed_note}"
+                ))
+                .components(vec![]),
+        )
+        .await?;
+    Ok(())
+}
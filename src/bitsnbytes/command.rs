@@ -0,0 +1,15 @@
+use crate::AppContext;
+use crate::AppError;
+use crate::bitsnbytes::fam::manage;
+use crate::bitsnbytes::points::{fam, leaderboard};
+
+#[poise::command(
+    prefix_command,
+    slash_command,
+    subcommands("leaderboard", "fam", "manage"),
+    guild_only
+)]
+pub(crate) async fn bnb(ctx: AppContext<'_>) -> Result<(), AppError> {
+    ctx.reply("base command is a noop").await?;
+    Ok(())
+}
@@ -0,0 +1,529 @@
+// Bits & Bytes fam registry, so meetups can be attributed by Discord user ID
+
+use anyhow::{Context as _, bail};
+use entity::{bnb_fam, bnb_fam_member, sea_orm_active_enums::BnbFamRole};
+use itertools::Itertools as _;
+use poise::ChoiceParameter;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, DatabaseConnection, DbErr, EntityTrait as _,
+    IntoActiveModel as _, JoinType, QueryFilter as _, QueryOrder as _, QuerySelect as _,
+    RelationTrait as _, TransactionTrait as _,
+    sea_query::{Expr, Func, OnConflict},
+};
+use serenity::all::{CreateEmbed, GuildId, Mentionable as _, User, UserId};
+use std::{collections::HashMap, num::NonZeroUsize};
+
+use crate::{
+    AppContext, AppError, AppVars,
+    bitsnbytes::{
+        assign::{assign, signup_message},
+        points::set_weights,
+    },
+    util::{
+        ContextExtras as _,
+        gsheets::get_spreadsheet_range,
+        paginate::{EmbedLinePaginator, PaginatorOptions},
+    },
+};
+
+#[derive(ChoiceParameter, PartialEq, Eq, Copy, Clone, Debug)]
+pub(crate) enum FamRole {
+    Bit,
+    Byte,
+}
+
+impl From<FamRole> for BnbFamRole {
+    fn from(role: FamRole) -> Self {
+        match role {
+            FamRole::Bit => Self::Bit,
+            FamRole::Byte => Self::Byte,
+        }
+    }
+}
+
+/// Every fam that any of `user_ids` is a bit or byte in
+pub(crate) async fn fams_of_users(
+    conn: &DatabaseConnection,
+    user_ids: &[UserId],
+) -> anyhow::Result<Vec<bnb_fam::Model>> {
+    let fams = bnb_fam::Entity::find()
+        .join(JoinType::InnerJoin, bnb_fam::Relation::BnbFamMember.def())
+        .filter(bnb_fam_member::Column::DiscordId.is_in(user_ids.iter().map(|id| id.get() as i64)))
+        .order_by_asc(bnb_fam::Column::Name)
+        .all(conn)
+        .await
+        .context("fetch fams of users")?;
+
+    Ok(fams.into_iter().unique_by(|fam| fam.id).collect_vec())
+}
+
+/// Looks up a fam by name, ignoring case
+pub(crate) async fn find_fam(
+    conn: &DatabaseConnection,
+    name: &str,
+) -> anyhow::Result<Option<bnb_fam::Model>> {
+    bnb_fam::Entity::find()
+        .filter(
+            Expr::expr(Func::lower(Expr::col(bnb_fam::Column::Name)))
+                .eq(name.trim().to_lowercase()),
+        )
+        .one(conn)
+        .await
+        .context("fetch fam")
+}
+
+async fn expect_fam(conn: &DatabaseConnection, name: &str) -> anyhow::Result<bnb_fam::Model> {
+    match find_fam(conn, name).await? {
+        Some(fam) => Ok(fam),
+        None => bail!("There is no fam named **{}**", name.trim()),
+    }
+}
+
+pub(crate) async fn fam_name_choices(ctx: AppContext<'_>, partial: &str) -> Vec<String> {
+    let names = bnb_fam::Entity::find()
+        .select_only()
+        .column(bnb_fam::Column::Name)
+        .filter(
+            Expr::expr(Func::lower(Expr::col(bnb_fam::Column::Name)))
+                .like(format!("%{}%", partial.to_lowercase())),
+        )
+        .order_by_asc(bnb_fam::Column::Name)
+        .limit(25)
+        .into_tuple::<String>()
+        .all(&ctx.data().db)
+        .await;
+
+    names.unwrap_or_default()
+}
+
+/// Mentions of the fam's bits or bytes, or a dash if it has none
+pub(crate) fn role_mentions(members: &[bnb_fam_member::Model], role: &BnbFamRole) -> String {
+    let mentions = members
+        .iter()
+        .filter(|member| member.role == *role)
+        .map(|member| UserId::new(member.discord_id as u64).mention())
+        .join(", ");
+    match mentions.is_empty() {
+        true => String::from("\u{2013}"),
+        false => mentions,
+    }
+}
+
+fn fam_line(fam: &bnb_fam::Model, members: &[bnb_fam_member::Model]) -> String {
+    format!(
+        "**{}**: bits {}; bytes {}",
+        fam.name,
+        role_mentions(members, &BnbFamRole::Bit),
+        role_mentions(members, &BnbFamRole::Byte)
+    )
+}
+
+#[poise::command(
+    slash_command,
+    subcommands(
+        "create_fam",
+        "rename_fam",
+        "delete_fam",
+        "add_member",
+        "remove_member",
+        "list_fams",
+        "import_fams",
+        "set_weights",
+        "signup_message",
+        "assign"
+    ),
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn manage(ctx: AppContext<'_>) -> Result<(), AppError> {
+    ctx.reply("base command is a noop").await?;
+    Ok(())
+}
+
+/// Create a Bits & Bytes fam
+#[poise::command(slash_command, rename = "create", ephemeral)]
+async fn create_fam(
+    ctx: AppContext<'_>,
+    #[description = "Name of the fam"] name: String,
+) -> Result<(), AppError> {
+    let conn = &ctx.data().db;
+    let name = name.trim();
+    if name.is_empty() {
+        bail!("Fam names can't be empty");
+    }
+    if let Some(fam) = find_fam(conn, name).await? {
+        bail!("There is already a fam named **{}**", fam.name);
+    }
+
+    let fam = bnb_fam::ActiveModel {
+        name: ActiveValue::Set(name.to_owned()),
+        ..Default::default()
+    };
+    bnb_fam::Entity::insert(fam)
+        .exec(conn)
+        .await
+        .context("create fam")?;
+
+    ctx.reply_ephemeral(format!(
+        "Created fam **{name}**. Add its bits and bytes with `/bnb manage add`"
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Rename a Bits & Bytes fam
+#[poise::command(slash_command, rename = "rename", ephemeral)]
+async fn rename_fam(
+    ctx: AppContext<'_>,
+    #[description = "Current name of the fam"]
+    #[autocomplete = "fam_name_choices"]
+    fam: String,
+    #[description = "New name for the fam"] new_name: String,
+) -> Result<(), AppError> {
+    let conn = &ctx.data().db;
+    let fam = expect_fam(conn, &fam).await?;
+    let new_name = new_name.trim().to_owned();
+    if new_name.is_empty() {
+        bail!("Fam names can't be empty");
+    }
+    if let Some(existing) = find_fam(conn, &new_name).await?
+        && existing.id != fam.id
+    {
+        bail!("There is already a fam named **{}**", existing.name);
+    }
+
+    let old_name = fam.name.clone();
+    let mut fam = fam.into_active_model();
+    fam.name = ActiveValue::Set(new_name.clone());
+    bnb_fam::Entity::update(fam)
+        .exec(conn)
+        .await
+        .context("rename fam")?;
+
+    ctx.reply_ephemeral(format!("Renamed **{old_name}** to **{new_name}**"))
+        .await?;
+    Ok(())
+}
+
+/// Delete a Bits & Bytes fam
+#[poise::command(slash_command, rename = "delete", ephemeral)]
+async fn delete_fam(
+    ctx: AppContext<'_>,
+    #[description = "Name of the fam"]
+    #[autocomplete = "fam_name_choices"]
+    fam: String,
+) -> Result<(), AppError> {
+    let conn = &ctx.data().db;
+    let fam = expect_fam(conn, &fam).await?;
+    bnb_fam::Entity::delete_by_id(fam.id)
+        .exec(conn)
+        .await
+        .context("delete fam")?;
+
+    ctx.reply_ephemeral(format!("Deleted fam **{}**", fam.name))
+        .await?;
+    Ok(())
+}
+
+/// Add a bit or byte to a fam, or change their role in it
+#[poise::command(slash_command, rename = "add", ephemeral)]
+async fn add_member(
+    ctx: AppContext<'_>,
+    #[description = "Name of the fam"]
+    #[autocomplete = "fam_name_choices"]
+    fam: String,
+    #[description = "Member to add"] member: User,
+    #[description = "Whether they are a bit or a byte"] role: FamRole,
+) -> Result<(), AppError> {
+    let conn = &ctx.data().db;
+    let fam = expect_fam(conn, &fam).await?;
+
+    let fam_member = bnb_fam_member::ActiveModel {
+        fam_id: ActiveValue::Set(fam.id),
+        discord_id: ActiveValue::Set(member.id.get() as i64),
+        role: ActiveValue::Set(role.into()),
+    };
+    bnb_fam_member::Entity::insert(fam_member)
+        .on_conflict(
+            OnConflict::columns([
+                bnb_fam_member::Column::FamId,
+                bnb_fam_member::Column::DiscordId,
+            ])
+            .update_column(bnb_fam_member::Column::Role)
+            .to_owned(),
+        )
+        .exec(conn)
+        .await
+        .context("add fam member")?;
+
+    ctx.reply_ephemeral(format!(
+        "{} is now a {} in **{}**",
+        member.mention(),
+        role.name().to_lowercase(),
+        fam.name
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Remove a bit or byte from their fam
+#[poise::command(slash_command, rename = "remove", ephemeral)]
+async fn remove_member(
+    ctx: AppContext<'_>,
+    #[description = "Member to remove"] member: User,
+    #[description = "Only remove them from this fam; removes them from every fam if empty"]
+    #[autocomplete = "fam_name_choices"]
+    fam: Option<String>,
+) -> Result<(), AppError> {
+    let conn = &ctx.data().db;
+    let mut query = bnb_fam_member::Entity::delete_many()
+        .filter(bnb_fam_member::Column::DiscordId.eq(member.id.get() as i64));
+    if let Some(fam) = fam {
+        let fam = expect_fam(conn, &fam).await?;
+        query = query.filter(bnb_fam_member::Column::FamId.eq(fam.id));
+    }
+
+    let removed = query.exec(conn).await.context("remove fam member")?;
+
+    ctx.reply_ephemeral(match removed.rows_affected {
+        0 => format!("{} is not in that fam", member.mention()),
+        _ => format!("Removed {} from their fam", member.mention()),
+    })
+    .await?;
+    Ok(())
+}
+
+/// List every Bits & Bytes fam with its bits and bytes
+#[poise::command(slash_command, rename = "list", ephemeral)]
+async fn list_fams(ctx: AppContext<'_>) -> Result<(), AppError> {
+    let fams = bnb_fam::Entity::find()
+        .order_by_asc(bnb_fam::Column::Name)
+        .find_with_related(bnb_fam_member::Entity)
+        .all(&ctx.data().db)
+        .await
+        .context("fetch fams")?;
+
+    if fams.is_empty() {
+        ctx.reply_ephemeral(
+            "There are no fams yet. Create them with `/bnb manage create` or `/bnb manage import`",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let embed = CreateEmbed::default()
+        .color(0xff87a6)
+        .title("Bits & Bytes Fams");
+    let paginator = EmbedLinePaginator::new(
+        fams.iter()
+            .map(|(fam, members)| fam_line(fam, members).into_boxed_str())
+            .collect_vec(),
+        PaginatorOptions::default()
+            .sep("\n".into())
+            .max_lines(NonZeroUsize::new(15).unwrap())
+            .ephemeral(true)
+            .embed(embed),
+    );
+
+    paginator.run(ctx).await.context("bnb fams paginate")?;
+    Ok(())
+}
+
+/// Lowercased usernames of everyone in the guild, so sheet cells can be resolved to user IDs
+pub(crate) async fn guild_usernames(
+    ctx: &serenity::all::Context,
+    guild_id: GuildId,
+) -> anyhow::Result<HashMap<String, UserId>> {
+    // same safeguards as fetching matchy participants
+    const MAX_PAGES: u64 = 20;
+    const PAGE_LIMIT: u64 = 1000;
+
+    let mut last_member = None;
+    let mut usernames = HashMap::new();
+
+    for _ in 0..MAX_PAGES {
+        let page = guild_id.members(ctx, Some(PAGE_LIMIT), last_member).await?;
+        usernames.extend(
+            page.iter()
+                .map(|member| (member.user.name.to_lowercase(), member.user.id)),
+        );
+
+        if page.len() < PAGE_LIMIT as usize {
+            break;
+        }
+        last_member = page.last().map(|member| member.user.id);
+    }
+
+    Ok(usernames)
+}
+
+/// Fams read from the B&B sheet: the name and each bit and byte cell, still unresolved
+struct SheetFam {
+    name: String,
+    members: Vec<(String, BnbFamRole)>,
+}
+
+/// Reads fams from `BNB_SHEET__LOOKUP_RANGE`, finding the name, bit and byte columns by the
+/// headers in the range's first row rather than by position
+async fn read_sheet_fams(data: &AppVars) -> anyhow::Result<Vec<SheetFam>> {
+    let rows = get_spreadsheet_range(
+        data.google_service_account.clone(),
+        &data.env.bnb_sheet.id,
+        &data.env.bnb_sheet.lookup_range,
+    )
+    .await?
+    .values;
+
+    let Some((header, rows)) = rows.split_first() else {
+        bail!("The B&B sheet range is empty");
+    };
+    let header = header.iter().map(|cell| cell.to_lowercase()).collect_vec();
+    let Some(name_col) = header
+        .iter()
+        .position(|cell| cell.contains("fam"))
+        .or_else(|| header.iter().position(|cell| cell.contains("name")))
+    else {
+        bail!("The first row of the B&B sheet range needs a \"Fam Name\" header");
+    };
+    let role_cols = header
+        .iter()
+        .enumerate()
+        .filter(|(col, _)| *col != name_col)
+        .filter_map(|(col, cell)| match cell {
+            _ if cell.contains("byte") => Some((col, BnbFamRole::Byte)),
+            _ if cell.contains("bit") => Some((col, BnbFamRole::Bit)),
+            _ => None,
+        })
+        .collect_vec();
+    if role_cols.is_empty() {
+        bail!("The first row of the B&B sheet range needs \"Bit\" and \"Byte\" headers");
+    }
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let name = row.get(name_col)?.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let members = role_cols
+                .iter()
+                .filter_map(|(col, role)| Some((row.get(*col)?, role)))
+                // a cell may list several people
+                .flat_map(|(cell, role)| {
+                    cell.split([',', '\n'])
+                        .map(|username| username.trim().trim_start_matches('@').to_lowercase())
+                        .filter(|username| !username.is_empty())
+                        .map(|username| (username, role.clone()))
+                })
+                .collect_vec();
+            Some(SheetFam {
+                name: name.to_owned(),
+                members,
+            })
+        })
+        .collect_vec())
+}
+
+/// Import fams from the B&B sheet, replacing the bits and bytes of any fam listed there
+#[poise::command(slash_command, rename = "import", guild_only, ephemeral)]
+async fn import_fams(ctx: AppContext<'_>) -> Result<(), AppError> {
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
+    ctx.defer_ephemeral().await?;
+    let data = ctx.data();
+
+    let sheet_fams = read_sheet_fams(data).await?;
+    let usernames = guild_usernames(ctx.serenity_context(), guild_id).await?;
+
+    let mut unresolved = Vec::new();
+    let fams = sheet_fams
+        .into_iter()
+        .map(|fam| {
+            let members = fam
+                .members
+                .into_iter()
+                .filter_map(|(username, role)| {
+                    // accept user IDs as well as usernames
+                    let user_id = username
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|&id| id != 0)
+                        .map(UserId::new)
+                        .or_else(|| usernames.get(&username).copied());
+                    if user_id.is_none() {
+                        unresolved.push(format!("`{username}` ({})", fam.name));
+                    }
+                    Some((user_id?, role))
+                })
+                .unique_by(|(user_id, _)| *user_id)
+                .collect_vec();
+            (fam.name, members)
+        })
+        .collect_vec();
+
+    let fam_count = fams.len();
+    let member_count = fams.iter().map(|(_, members)| members.len()).sum::<usize>();
+    data.db
+        .transaction::<_, (), DbErr>(|txn| {
+            Box::pin(async move {
+                for (name, members) in fams {
+                    // match existing fams ignoring case, like find_fam
+                    let existing = bnb_fam::Entity::find()
+                        .filter(
+                            Expr::expr(Func::lower(Expr::col(bnb_fam::Column::Name)))
+                                .eq(name.to_lowercase()),
+                        )
+                        .one(txn)
+                        .await?;
+                    let fam_id = if let Some(fam) = existing {
+                        fam.id
+                    } else {
+                        let fam = bnb_fam::ActiveModel {
+                            name: ActiveValue::Set(name),
+                            ..Default::default()
+                        };
+                        bnb_fam::Entity::insert(fam).exec(txn).await?.last_insert_id
+                    };
+
+                    bnb_fam_member::Entity::delete_many()
+                        .filter(bnb_fam_member::Column::FamId.eq(fam_id))
+                        .exec(txn)
+                        .await?;
+                    if members.is_empty() {
+                        continue;
+                    }
+                    bnb_fam_member::Entity::insert_many(members.into_iter().map(
+                        |(user_id, role)| bnb_fam_member::ActiveModel {
+                            fam_id: ActiveValue::Set(fam_id),
+                            discord_id: ActiveValue::Set(user_id.get() as i64),
+                            role: ActiveValue::Set(role),
+                        },
+                    ))
+                    .exec(txn)
+                    .await?;
+                }
+                Ok(())
+            })
+        })
+        .await
+        .context("import fams")?;
+
+    // keep the reply under Discord's message length limit
+    const MAX_LISTED: usize = 20;
+    let unresolved_note = match unresolved.len() {
+        0 => String::new(),
+        count => format!(
+            "\nThese usernames are not in this server, so add them with `/bnb manage add`: {}{}",
+            unresolved.iter().take(MAX_LISTED).join(", "),
+            match count.saturating_sub(MAX_LISTED) {
+                0 => String::new(),
+                more => format!(" and {more} more"),
+            }
+        ),
+    };
+
+    ctx.reply_ephemeral(format!(
+        "Imported {fam_count} fams with {member_count} bits and bytes.{unresolved_note}"
+    ))
+    .await?;
+    Ok(())
+}
@@ -2,16 +2,30 @@
 
 use crate::{
     AppContext, AppError, AppVars,
+    bitsnbytes::{
+        fam::{fams_of_users, find_fam},
+        points::meetup_type_label,
+    },
     util::{
+        ContextExtras as _,
+        components::{FLOW_TIMEOUT, choice_select_row, confirm_buttons_row},
         gforms::submit_google_form,
-        gsheets::{SheetsResponse, get_spreadsheet_range},
-        modal::ModalInputTexts,
+        message::get_members,
     },
 };
 use anyhow::{Context as _, anyhow, bail};
+use chrono::Utc;
+use entity::{bnb_fam, bnb_meetup, bnb_meetup_fam, sea_orm_active_enums::BnbMeetupType};
+use itertools::Itertools as _;
+use poise::CreateReply;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, DatabaseConnection, DbErr, EntityTrait as _, Iterable as _,
+    QueryFilter as _, SqlErr, TransactionError, TransactionTrait as _,
+};
 use serenity::all::{
-    CacheHttp as _, CreateActionRow, CreateInputText, CreateInteractionResponse, CreateModal,
-    EditInteractionResponse, InputTextStyle, ModalInteraction, ReactionType,
+    ComponentInteractionDataKind, CreateActionRow, CreateInputText, CreateInteractionResponse,
+    CreateInteractionResponseMessage, CreateQuickModal, InputTextStyle, Mentionable as _, Message,
+    ReactionType, UserId,
 };
 
 async fn submit_bnb_gform(
@@ -41,115 +55,257 @@ async fn submit_bnb_gform(
     Ok(())
 }
 
-async fn get_overview_range(data: &AppVars) -> anyhow::Result<SheetsResponse> {
-    let sheet_id = &data.env.bnb_sheet.id;
-    let range = &data.env.bnb_sheet.lookup_range;
-
-    let resp = get_spreadsheet_range(data.google_service_account.clone(), sheet_id, range).await?;
+/// The meetup already logged from `message`, with the fams it credits
+async fn logged_meetup(
+    conn: &DatabaseConnection,
+    message: &Message,
+) -> anyhow::Result<Option<(bnb_meetup::Model, Vec<bnb_fam::Model>)>> {
+    Ok(bnb_meetup::Entity::find()
+        .filter(bnb_meetup::Column::MessageId.eq(message.id.get() as i64))
+        .find_with_related(bnb_fam::Entity)
+        .all(conn)
+        .await
+        .context("fetch logged meetup")?
+        .into_iter()
+        .next())
+}
 
-    Ok(resp)
+fn already_logged(meetup: &bnb_meetup::Model, fams: &[bnb_fam::Model]) -> String {
+    format!(
+        "This message was already logged as a {} meetup for {} by {} on <t:{}:d>",
+        meetup_type_label(&meetup.meetup_type),
+        fams.iter()
+            .map(|fam| format!("**{}**", fam.name))
+            .join(", "),
+        UserId::new(meetup.logged_by as u64).mention(),
+        meetup.logged_at.and_utc().timestamp()
+    )
 }
 
-#[poise::command(context_menu_command = "Log B&B Meetup", guild_only)]
-pub(crate) async fn log_bnb_meetup_message(
+/// Records one meetup crediting every fam in `fam_names` (one per line), then mirrors it to the
+/// B&B Google Form once per fam. Failing to mirror is not an error since the database is the
+/// source of truth.
+async fn record_meetup(
     ctx: AppContext<'_>,
-    message: serenity::all::Message,
-) -> Result<(), AppError> {
-    let submitter = &message.author.name;
+    fam_names: &str,
+    message: &Message,
+    meetup_type: &str,
+) -> anyhow::Result<()> {
+    let data = ctx.data();
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
 
-    // get fam name from submitter
-    let range = get_overview_range(ctx.data()).await?;
-    let fam_name = range
-        .values
-        .into_iter()
-        .find(|row| {
-            if row.len() != 9 {
-                return false;
-            }
+    let mut fams = Vec::new();
+    let mut unknown = Vec::new();
+    for name in fam_names
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        match find_fam(&data.db, name).await? {
+            Some(fam) => fams.push(fam),
+            None => unknown.push(format!("**{name}**")),
+        }
+    }
+    if !unknown.is_empty() {
+        bail!(
+            "There is no fam named {}. Check `/bnb manage list` for the right names.",
+            unknown.join(", ")
+        );
+    }
+    let fams = fams.into_iter().unique_by(|fam| fam.id).collect_vec();
+    let meetup_type = BnbMeetupType::iter()
+        .find(|it| meetup_type_label(it) == meetup_type)
+        .unwrap_or(BnbMeetupType::Hangout);
+    match fams.len() {
+        0 => bail!("Please enter at least one fam name"),
+        1 => {}
+        _ if meetup_type != BnbMeetupType::Joint => {
+            bail!("Meetups with more than one fam must be logged as Joint")
+        }
+        _ => {}
+    }
 
-            let byte1 = &row[7];
-            let byte2 = &row[8];
+    if let Some((meetup, fams)) = logged_meetup(&data.db, message).await? {
+        bail!(already_logged(&meetup, &fams));
+    }
 
-            submitter == byte1 || submitter == byte2
+    let meetup = bnb_meetup::ActiveModel {
+        meetup_type: ActiveValue::Set(meetup_type.clone()),
+        guild_id: ActiveValue::Set(guild_id.get() as i64),
+        channel_id: ActiveValue::Set(message.channel_id.get() as i64),
+        message_id: ActiveValue::Set(message.id.get() as i64),
+        met_at: ActiveValue::Set(message.timestamp.naive_utc()),
+        logged_by: ActiveValue::Set(ctx.author().id.get() as i64),
+        logged_at: ActiveValue::Set(Utc::now().naive_utc()),
+        ..Default::default()
+    };
+    let fam_ids = fams.iter().map(|fam| fam.id).collect_vec();
+    let inserted = data
+        .db
+        .transaction::<_, (), DbErr>(|txn| {
+            Box::pin(async move {
+                let meetup = bnb_meetup::Entity::insert(meetup)
+                    .exec_with_returning(txn)
+                    .await?;
+                bnb_meetup_fam::Entity::insert_many(fam_ids.into_iter().map(|fam_id| {
+                    bnb_meetup_fam::ActiveModel {
+                        meetup_id: ActiveValue::Set(meetup.id),
+                        fam_id: ActiveValue::Set(fam_id),
+                    }
+                }))
+                .exec(txn)
+                .await?;
+                Ok(())
+            })
         })
-        .map_or(String::from(""), |row| row[0].clone());
-
-    let msg_input = CreateActionRow::InputText(
-        CreateInputText::new(InputTextStyle::Short, "Message Link", "message_link")
-            .value(message.link())
-            .required(true),
-    );
-
-    let fam_name_input = CreateActionRow::InputText(
-        CreateInputText::new(
-            InputTextStyle::Short,
-            "Fam Name (case sensitive)",
-            "fam_name",
-        )
-        .value(fam_name)
-        .required(true),
-    );
-
-    let meetup_type_input = CreateActionRow::InputText(
-        CreateInputText::new(
-            InputTextStyle::Short,
-            "Type (Hangout | Joint | Official B&B)",
-            "meetup_type",
-        )
-        .value("Hangout")
-        .required(true),
-    );
+        .await;
+    match inserted {
+        Ok(()) => {}
+        // someone else logged the same message in the meantime
+        Err(TransactionError::Transaction(err))
+            if matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) =>
+        {
+            bail!("This message was just logged by someone else");
+        }
+        Err(err) => return Err(err).context("insert meetup"),
+    }
 
-    let modal = CreateModal::new("bnb_meetup_log_modal", "Log Bits & Bytes Meetup")
-        .components(vec![msg_input, fam_name_input, meetup_type_input]);
+    for fam in &fams {
+        let label = meetup_type_label(&meetup_type);
+        if let Err(why) = submit_bnb_gform(data, &fam.name, &message.link(), label).await {
+            dbg!(why);
+        }
+    }
 
-    let reply = CreateInteractionResponse::Modal(modal);
-    let AppContext::Application(ctx) = ctx else {
-        bail!("unexpected context type")
-    };
+    Ok(())
+}
 
-    ctx.interaction.create_response(ctx.http(), reply).await?;
+const MEETUP_TYPES: [&str; 3] = ["Hangout", "Joint", "Official B&B"];
 
-    Ok(())
+fn meetup_components(meetup_type: &str) -> Vec<CreateActionRow> {
+    vec![
+        choice_select_row("bnb_meetup_type", &MEETUP_TYPES, meetup_type),
+        confirm_buttons_row("bnb_meetup"),
+    ]
 }
 
-pub(crate) async fn confirm_bnb_meetup_modal(
-    ctx: &serenity::all::Context,
-    data: &AppVars,
-    ixn: &ModalInteraction,
+#[poise::command(context_menu_command = "Log B&B Meetup", guild_only)]
+pub(crate) async fn log_bnb_meetup_message(
+    ctx: AppContext<'_>,
+    message: Message,
 ) -> Result<(), AppError> {
-    let inputs = ModalInputTexts::new(ixn);
+    if let Some((meetup, fams)) = logged_meetup(&ctx.data().db, &message).await? {
+        ctx.reply_ephemeral(already_logged(&meetup, &fams)).await?;
+        return Ok(());
+    }
 
-    let message_link = inputs.get_required_value("message_link")?;
-    let fam_name = inputs.get_required_value("fam_name")?;
-    let meetup_type = inputs.get_required_value("meetup_type")?;
-
-    ixn.defer_ephemeral(ctx.http()).await?;
+    // prefill the fams of whoever posted the meetup and everyone they mentioned
+    let fams = fams_of_users(&ctx.data().db, &get_members(&message, true)).await?;
+    let mut fam_names = fams.iter().map(|fam| fam.name.as_str()).join("\n");
+    let mut meetup_type = match fams.len() {
+        0 | 1 => MEETUP_TYPES[0],
+        _ => MEETUP_TYPES[1],
+    };
 
-    let message = message_link
-        .split('/')
-        .next_back()
-        .ok_or(anyhow!("Invalid link"))
-        .and_then(|id| {
-            id.parse::<u64>()
-                .context("unexpected non-numerical message ID")
-        })
-        .map(|id| ixn.channel_id.message(ctx.http(), id))
-        .context("Cannot find the original message")?
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(format!("Logging B&B meetup for {}", message.link()))
+                .components(meetup_components(meetup_type))
+                .ephemeral(true),
+        )
         .await?;
+    let sent = handle.message().await?;
 
-    // submit the B&B Google Form
-    let response = match submit_bnb_gform(data, &fam_name, &message_link, &meetup_type).await {
-        Ok(_) => "ok, logged",
-        Err(why) => &why.to_string(),
-    };
+    while let Some(ixn) = sent
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(FLOW_TIMEOUT)
+        .await
+    {
+        match (ixn.data.custom_id.as_str(), &ixn.data.kind) {
+            ("bnb_meetup_type", ComponentInteractionDataKind::StringSelect { values }) => {
+                meetup_type = MEETUP_TYPES
+                    .into_iter()
+                    .find(|&it| values.iter().any(|value| value == it))
+                    .unwrap_or(MEETUP_TYPES[0]);
+                ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+                    .await?;
+            }
+            ("bnb_meetup_confirm", _) => {
+                let fam_names_input = CreateInputText::new(
+                    InputTextStyle::Paragraph,
+                    "Fam Names (one per line)",
+                    "fam_names",
+                )
+                .value(&fam_names)
+                .required(true);
+                let Some(modal) = ixn
+                    .quick_modal(
+                        ctx.serenity_context(),
+                        CreateQuickModal::new("Log Bits & Bytes Meetup")
+                            .field(fam_names_input)
+                            .timeout(FLOW_TIMEOUT),
+                    )
+                    .await?
+                else {
+                    continue;
+                };
+                modal
+                    .interaction
+                    .create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+                    .await?;
+                fam_names = modal.inputs.into_iter().next().unwrap_or_default();
 
-    ixn.edit_response(ctx.http(), EditInteractionResponse::new().content(response))
-        .await?;
+                let submitted = record_meetup(ctx, &fam_names, &message, meetup_type).await;
 
-    let _ = message
-        .react(ctx.http(), ReactionType::Unicode("👫".to_owned()))
-        .await;
+                let (content, components, done) = match submitted {
+                    Ok(()) => (String::from("ok, logged"), vec![], true),
+                    Err(why) => (why.to_string(), meetup_components(meetup_type), false),
+                };
+                handle
+                    .edit(
+                        ctx,
+                        CreateReply::default()
+                            .content(content)
+                            .components(components),
+                    )
+                    .await?;
+
+                if done {
+                    let _ = message
+                        .react(ctx.http(), ReactionType::Unicode("👫".to_owned()))
+                        .await;
+                    return Ok(());
+                }
+            }
+            ("bnb_meetup_cancel", _) => {
+                ixn.create_response(
+                    ctx.http(),
+                    CreateInteractionResponse::UpdateMessage(
+                        CreateInteractionResponseMessage::new()
+                            .content("Cancelled, the meetup was not logged")
+                            .components(vec![]),
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+            _ => {
+                ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+                    .await?;
+            }
+        }
+    }
+
+    handle
+        .edit(
+            ctx,
+            CreateReply::default()
+                .content("Timed out, the meetup was not logged")
+                .components(vec![]),
+        )
+        .await?;
 
     Ok(())
 }
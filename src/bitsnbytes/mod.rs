@@ -1 +1,5 @@
+pub(crate) mod assign;
+pub(crate) mod command;
+pub(crate) mod fam;
 pub(crate) mod meetup;
+pub(crate) mod points;
@@ -0,0 +1,349 @@
+// Bits & Bytes fam points, from logged meetups weighted by their type
+
+use anyhow::Context as _;
+use chrono::NaiveDateTime;
+use entity::{
+    bnb_fam, bnb_fam_member, bnb_meetup, bnb_meetup_weight,
+    sea_orm_active_enums::{BnbFamRole, BnbMeetupType},
+};
+use itertools::Itertools as _;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, DatabaseConnection, EntityTrait as _, Iterable as _,
+    QueryFilter as _, QueryOrder as _, sea_query::OnConflict,
+};
+use serenity::all::{CreateEmbed, CreateEmbedFooter};
+use std::num::NonZeroUsize;
+
+use crate::{
+    AppContext, AppError,
+    attendance::period::AttendancePeriod,
+    bitsnbytes::fam::{fam_name_choices, find_fam, role_mentions},
+    util::{
+        ContextExtras as _,
+        paginate::{EmbedLinePaginator, PaginatorOptions},
+        time,
+    },
+};
+
+/// The label each meetup type has in the logging menu and the B&B Google Form
+pub(crate) fn meetup_type_label(meetup_type: &BnbMeetupType) -> &'static str {
+    match meetup_type {
+        BnbMeetupType::Hangout => "Hangout",
+        BnbMeetupType::Joint => "Joint",
+        BnbMeetupType::Official => "Official B&B",
+    }
+}
+
+/// Points for one meetup of each type, used for any type that was never configured
+fn default_points(meetup_type: &BnbMeetupType) -> i32 {
+    match meetup_type {
+        BnbMeetupType::Hangout => 1,
+        BnbMeetupType::Joint => 2,
+        BnbMeetupType::Official => 3,
+    }
+}
+
+/// Points for one meetup of each type, in `BnbMeetupType::iter()` order
+pub(crate) struct MeetupWeights(Vec<(BnbMeetupType, i32)>);
+
+impl MeetupWeights {
+    pub(crate) async fn fetch(conn: &DatabaseConnection) -> anyhow::Result<Self> {
+        let configured = bnb_meetup_weight::Entity::find()
+            .all(conn)
+            .await
+            .context("fetch meetup weights")?;
+
+        Ok(Self(
+            BnbMeetupType::iter()
+                .map(|meetup_type| {
+                    let points = configured
+                        .iter()
+                        .find(|weight| weight.meetup_type == meetup_type)
+                        .map_or_else(|| default_points(&meetup_type), |weight| weight.points);
+                    (meetup_type, points)
+                })
+                .collect_vec(),
+        ))
+    }
+
+    pub(crate) fn of(&self, meetup_type: &BnbMeetupType) -> i64 {
+        self.0
+            .iter()
+            .find(|(it, _)| it == meetup_type)
+            .map_or(0, |(_, points)| i64::from(*points))
+    }
+
+    fn summary(&self) -> String {
+        self.0
+            .iter()
+            .map(|(meetup_type, points)| format!("{}: {points}", meetup_type_label(meetup_type)))
+            .join(", ")
+    }
+}
+
+/// Meetups between `start` and `end`, newest first, with the fams each one credits
+async fn meetups_between(
+    conn: &DatabaseConnection,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> anyhow::Result<Vec<(bnb_meetup::Model, Vec<bnb_fam::Model>)>> {
+    bnb_meetup::Entity::find()
+        .filter(bnb_meetup::Column::MetAt.gte(start))
+        .filter(bnb_meetup::Column::MetAt.lt(end))
+        .order_by_desc(bnb_meetup::Column::MetAt)
+        .find_with_related(bnb_fam::Entity)
+        .all(conn)
+        .await
+        .context("fetch meetups")
+}
+
+/// Whether a meetup crediting `credited` counts toward `fam`; joint meetups count in full for
+/// every fam
+fn credits(credited: &[bnb_fam::Model], fam: &bnb_fam::Model) -> bool {
+    credited.iter().any(|it| it.id == fam.id)
+}
+
+/// Counts of each meetup type, e.g. "2 Hangout, 1 Joint"
+fn type_counts(meetups: &[&bnb_meetup::Model]) -> String {
+    let counts = BnbMeetupType::iter()
+        .filter_map(|meetup_type| {
+            match meetups
+                .iter()
+                .filter(|meetup| meetup.meetup_type == meetup_type)
+                .count()
+            {
+                0 => None,
+                count => Some(format!("{count} {}", meetup_type_label(&meetup_type))),
+            }
+        })
+        .join(", ");
+    match counts.is_empty() {
+        true => String::from("no meetups"),
+        false => counts,
+    }
+}
+
+fn meetup_link(meetup: &bnb_meetup::Model) -> String {
+    format!(
+        "https://discord.com/channels/{}/{}/{}",
+        meetup.guild_id, meetup.channel_id, meetup.message_id
+    )
+}
+
+/// Rank Bits & Bytes fams by points from their meetups
+#[poise::command(slash_command, guild_only)]
+pub(crate) async fn leaderboard(
+    ctx: AppContext<'_>,
+    #[description = "Time period to count; defaults to this school year"] period: Option<
+        AttendancePeriod,
+    >,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    let conn = &ctx.data().db;
+    let tz = ctx.data().timezone;
+
+    let period = period.unwrap_or(AttendancePeriod::SchoolYear);
+    let (start, end, period_name) = period.bounds(tz, time::today(tz));
+
+    let fams = bnb_fam::Entity::find()
+        .all(conn)
+        .await
+        .context("fetch fams")?;
+    if fams.is_empty() {
+        ctx.reply_ephemeral("There are no Bits & Bytes fams yet")
+            .await?;
+        return Ok(());
+    }
+
+    let weights = MeetupWeights::fetch(conn).await?;
+    let meetups = meetups_between(conn, start, end).await?;
+
+    let lines = fams
+        .iter()
+        .map(|fam| {
+            let fam_meetups = meetups
+                .iter()
+                .filter(|(_, credited)| credits(credited, fam))
+                .map(|(meetup, _)| meetup)
+                .collect_vec();
+            let points = fam_meetups
+                .iter()
+                .map(|meetup| weights.of(&meetup.meetup_type))
+                .sum::<i64>();
+            (fam, points, fam_meetups)
+        })
+        .sorted_by(|(a_fam, a, _), (b_fam, b, _)| {
+            b.cmp(a).then_with(|| a_fam.name.cmp(&b_fam.name))
+        })
+        .enumerate()
+        .map(|(i, (fam, points, fam_meetups))| {
+            format!(
+                "{}. **{}**: {points} points ({})",
+                i + 1,
+                fam.name,
+                type_counts(&fam_meetups)
+            )
+            .into_boxed_str()
+        })
+        .collect_vec();
+
+    let embed = CreateEmbed::default()
+        .color(0xff87a6)
+        .title(format!("Bits & Bytes Leaderboard ({period_name})"))
+        .footer(CreateEmbedFooter::new(format!(
+            "Points per meetup: {}",
+            weights.summary()
+        )));
+    let paginator = EmbedLinePaginator::new(
+        lines,
+        PaginatorOptions::default()
+            .sep("\n".into())
+            .max_lines(NonZeroUsize::new(15).unwrap())
+            .ephemeral(true)
+            .embed(embed),
+    );
+
+    paginator
+        .run(ctx)
+        .await
+        .context("bnb leaderboard paginate")?;
+    Ok(())
+}
+
+/// Show a Bits & Bytes fam's members, points and meetup history
+#[poise::command(slash_command, guild_only)]
+pub(crate) async fn fam(
+    ctx: AppContext<'_>,
+    #[description = "Name of the fam"]
+    #[autocomplete = "fam_name_choices"]
+    name: String,
+    #[description = "Time period to show; defaults to this school year"] period: Option<
+        AttendancePeriod,
+    >,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    let conn = &ctx.data().db;
+    let tz = ctx.data().timezone;
+
+    let Some(fam) = find_fam(conn, &name).await? else {
+        ctx.reply_ephemeral(format!("There is no fam named **{}**", name.trim()))
+            .await?;
+        return Ok(());
+    };
+
+    let period = period.unwrap_or(AttendancePeriod::SchoolYear);
+    let (start, end, period_name) = period.bounds(tz, time::today(tz));
+
+    let members = bnb_fam_member::Entity::find()
+        .filter(bnb_fam_member::Column::FamId.eq(fam.id))
+        .all(conn)
+        .await
+        .context("fetch fam members")?;
+    let weights = MeetupWeights::fetch(conn).await?;
+    let meetups = meetups_between(conn, start, end)
+        .await?
+        .into_iter()
+        .filter(|(_, credited)| credits(credited, &fam))
+        .collect_vec();
+    let fam_meetups = meetups.iter().map(|(meetup, _)| meetup).collect_vec();
+    let points = fam_meetups
+        .iter()
+        .map(|meetup| weights.of(&meetup.meetup_type))
+        .sum::<i64>();
+
+    let mut lines = meetups
+        .iter()
+        .map(|(meetup, credited)| {
+            let others = credited
+                .iter()
+                .filter(|other| other.id != fam.id)
+                .map(|other| format!("**{}**", other.name))
+                .join(", ");
+            let with = match others.is_empty() {
+                true => String::new(),
+                false => format!(" with {others}"),
+            };
+            format!(
+                "<t:{}:d> **{}**{with} (+{}) [message]({})",
+                meetup.met_at.and_utc().timestamp(),
+                meetup_type_label(&meetup.meetup_type),
+                weights.of(&meetup.meetup_type),
+                meetup_link(meetup)
+            )
+            .into_boxed_str()
+        })
+        .collect_vec();
+    if lines.is_empty() {
+        lines.push(format!("No meetups were logged in {period_name}").into_boxed_str());
+    }
+
+    let embed = CreateEmbed::default()
+        .color(0xff87a6)
+        .title(format!("{} ({period_name})", fam.name))
+        .field("Bits", role_mentions(&members, &BnbFamRole::Bit), true)
+        .field("Bytes", role_mentions(&members, &BnbFamRole::Byte), true)
+        .field(
+            "Points",
+            format!("{points} ({})", type_counts(&fam_meetups)),
+            false,
+        );
+    let paginator = EmbedLinePaginator::new(
+        lines,
+        PaginatorOptions::default()
+            .sep("\n".into())
+            .max_lines(NonZeroUsize::new(15).unwrap())
+            .ephemeral(true)
+            .embed(embed),
+    );
+
+    paginator.run(ctx).await.context("bnb fam paginate")?;
+    Ok(())
+}
+
+/// Set how many points each type of meetup is worth, or show the current weights
+#[poise::command(slash_command, rename = "weights", ephemeral)]
+pub(crate) async fn set_weights(
+    ctx: AppContext<'_>,
+    #[description = "Points for a hangout"]
+    #[min = 0]
+    hangout: Option<u32>,
+    #[description = "Points for a joint meetup"]
+    #[min = 0]
+    joint: Option<u32>,
+    #[description = "Points for an official B&B event"]
+    #[min = 0]
+    official: Option<u32>,
+) -> Result<(), AppError> {
+    let conn = &ctx.data().db;
+
+    let updates = [
+        (BnbMeetupType::Hangout, hangout),
+        (BnbMeetupType::Joint, joint),
+        (BnbMeetupType::Official, official),
+    ]
+    .into_iter()
+    .filter_map(|(meetup_type, points)| {
+        Some(bnb_meetup_weight::ActiveModel {
+            meetup_type: ActiveValue::Set(meetup_type),
+            points: ActiveValue::Set(i32::try_from(points?).unwrap_or(i32::MAX)),
+        })
+    })
+    .collect_vec();
+
+    if !updates.is_empty() {
+        bnb_meetup_weight::Entity::insert_many(updates)
+            .on_conflict(
+                OnConflict::column(bnb_meetup_weight::Column::MeetupType)
+                    .update_column(bnb_meetup_weight::Column::Points)
+                    .to_owned(),
+            )
+            .exec(conn)
+            .await
+            .context("save meetup weights")?;
+    }
+
+    let weights = MeetupWeights::fetch(conn).await?;
+    ctx.reply_ephemeral(format!("Points per meetup: {}", weights.summary()))
+        .await?;
+    Ok(())
+}
@@ -1,12 +1,18 @@
 use crate::AppVars;
-use crate::attendance::checkin::confirm_attendance_log_modal;
-use crate::bitsnbytes::meetup::confirm_bnb_meetup_modal;
+use crate::attendance::requirements::send_attendance_nudges;
+use crate::attendance::window::resume_code_messages;
+use crate::bitsnbytes::assign::BnbSignup;
 use crate::matchy::opt_in::MatchyMeetupOptIn;
+use crate::roster::desynced::sync_discord_roles_nightly;
+use crate::roster::feed::post_roster_changes;
+use crate::roster::link::RosterLinkApproval;
 use crate::spottings::check_victim::check_message_snipe_victim;
-use crate::spottings::log::confirm_message_spotting_modal;
 use crate::spottings::privacy::SnipesOptOut;
 use crate::spottings::socials_role::SocialsParticipation;
 use crate::util::text::bot_invite_url;
+use crate::util::time::next_local_time;
+use chrono::{NaiveTime, Utc};
+use entity::sea_orm_active_enums::BnbFamRole;
 use rand::seq::IndexedRandom as _;
 use serenity::all::{
     ActivityData, ActivityType, CacheHttp as _, CreateInteractionResponse,
@@ -14,9 +20,16 @@ use serenity::all::{
     OnlineStatus, Permissions, Ready,
 };
 use serenity::async_trait;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::time;
 
+/// Local hour, in the club timezone, at which committee roles are synced when enabled
+const NIGHTLY_ROLE_SYNC_HOUR: u32 = 3;
+static ROLE_SYNC_SCHEDULED: AtomicBool = AtomicBool::new(false);
+static ROSTER_FEED_SCHEDULED: AtomicBool = AtomicBool::new(false);
+static NUDGES_SCHEDULED: AtomicBool = AtomicBool::new(false);
+
 pub(crate) struct LaikaEventHandler {
     pub(crate) data: AppVars,
 }
@@ -41,6 +54,74 @@ impl EventHandler for LaikaEventHandler {
             bot_invite_url(data_about_bot.user.id, Permissions::empty(), true)
         );
 
+        match resume_code_messages(ctx.http.clone(), self.data.db.clone()).await {
+            Ok(resumed) => println!("resumed {resumed} check-in code messages"),
+            Err(why) => {
+                dbg!(why);
+            }
+        }
+
+        // ready fires again on reconnects, which shouldn't start a second copy of these loops
+        if !ROSTER_FEED_SCHEDULED.swap(true, Ordering::SeqCst) {
+            let feed_ctx = ctx.clone();
+            let data = self.data.clone();
+            tokio::spawn(async move {
+                let mut interval = time::interval(Duration::from_secs(60 * 60));
+
+                loop {
+                    interval.tick().await;
+                    match post_roster_changes(&feed_ctx, &data).await {
+                        Ok(0) => {}
+                        Ok(posted) => println!("posted {posted} roster changes"),
+                        Err(why) => {
+                            dbg!(why);
+                        }
+                    }
+                }
+            });
+        }
+
+        if !NUDGES_SCHEDULED.swap(true, Ordering::SeqCst) {
+            let nudge_ctx = ctx.clone();
+            let data = self.data.clone();
+            tokio::spawn(async move {
+                let mut interval = time::interval(Duration::from_secs(24 * 60 * 60));
+
+                loop {
+                    interval.tick().await;
+                    match send_attendance_nudges(&nudge_ctx, &data).await {
+                        Ok(sent) => println!("sent {sent} attendance nudges"),
+                        Err(why) => {
+                            dbg!(why);
+                        }
+                    }
+                }
+            });
+        }
+
+        if self.data.nightly_role_sync && !ROLE_SYNC_SCHEDULED.swap(true, Ordering::SeqCst) {
+            let role_sync_ctx = ctx.clone();
+            let data = self.data.clone();
+            tokio::spawn(async move {
+                let sync_at =
+                    NaiveTime::from_hms_opt(NIGHTLY_ROLE_SYNC_HOUR, 0, 0).expect("valid hour");
+
+                loop {
+                    // wait first, so restarts and reconnects don't change roles right away
+                    let next = next_local_time(data.timezone, sync_at);
+                    let wait = (next - Utc::now().naive_utc()).to_std().unwrap_or_default();
+                    time::sleep(wait).await;
+
+                    match sync_discord_roles_nightly(&role_sync_ctx, &data).await {
+                        Ok(synced) => println!("synced committee roles for {synced} members"),
+                        Err(why) => {
+                            dbg!(why);
+                        }
+                    }
+                }
+            });
+        }
+
         tokio::spawn(async move {
             let mut interval = time::interval(Duration::from_secs(120));
 
@@ -120,17 +201,25 @@ impl EventHandler for LaikaEventHandler {
                         .check(interaction)
                         .await
                 }
-                _ => Ok(()),
-            },
-            Interaction::Modal(interaction) => match interaction.data.custom_id.as_str() {
-                "spotting_modal_confirm" => {
-                    confirm_message_spotting_modal(&ctx, &self.data, interaction).await
+                "roster_link_approve" => {
+                    RosterLinkApproval::new(&ctx, &self.data)
+                        .approve(interaction)
+                        .await
+                }
+                "roster_link_deny" => {
+                    RosterLinkApproval::new(&ctx, &self.data)
+                        .deny(interaction)
+                        .await
                 }
-                "attendance_log_modal_confirm" => {
-                    confirm_attendance_log_modal(&ctx, &self.data, interaction).await
+                "bnb_signup_bit" => {
+                    BnbSignup::new(&ctx, &self.data)
+                        .open(interaction, BnbFamRole::Bit)
+                        .await
                 }
-                "bnb_meetup_log_modal" => {
-                    confirm_bnb_meetup_modal(&ctx, &self.data, interaction).await
+                "bnb_signup_byte" => {
+                    BnbSignup::new(&ctx, &self.data)
+                        .open(interaction, BnbFamRole::Byte)
+                        .await
                 }
                 _ => Ok(()),
             },
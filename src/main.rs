@@ -4,6 +4,7 @@ mod handler;
 mod internal_commands;
 mod matchy;
 mod meta;
+mod permissions;
 mod roster;
 mod routes;
 mod server;
@@ -13,10 +14,11 @@ mod spottings;
 mod util;
 
 use crate::setup::{
-    ChannelVars, HttpVars, RoleVars, create_bot_framework_options, register_commands,
+    ChannelVars, HttpVars, RoleVars, create_bot_framework_options, parse_flag, register_commands,
 };
 use crate::util::gdrive::GoogleServiceAccount;
 use crate::util::roster::Roster;
+use crate::util::roster_source::roster_source_from_env;
 use anyhow::Context as _;
 use clap::ValueHint;
 use env_vars_struct::env_vars_struct;
@@ -34,6 +36,7 @@ env_vars_struct!(
     "APP__JWT_SECRET",
     "APP__ORIGIN",
     "APP__PORT",
+    "APP__TIMEZONE",
     "ATTENDANCE_FORM__ID",
     "ATTENDANCE_FORM__TOKEN_INPUT_ID",
     "ATTENDANCE_FORM__TOKEN_INPUT_VALUE",
@@ -48,6 +51,7 @@ env_vars_struct!(
     "BNB_SHEET__LOOKUP_RANGE",
     "BOT__COMMANDS__REGISTER_GLOBALLY",
     "BOT__COMMANDS__GUILDS",
+    "BOT__CHANNELS__BOT_LOG",
     "BOT__CHANNELS__ICSSC_GUILD_ID",
     "BOT__CHANNELS__MATCHY",
     "BOT__CHANNELS__SPOTTINGS",
@@ -55,8 +59,13 @@ env_vars_struct!(
     "BOT__ROLES__SOCIALS_PING",
     "GOOGLE_OAUTH_CLIENT__ID",
     "GOOGLE_OAUTH_CLIENT__SECRET",
+    "ROSTER_LINK_FORM__ID",
+    "ROSTER_LINK_FORM__CODE_INPUT_ID",
+    "ROSTER_SOURCE__KIND",
+    "ROSTER_SOURCE__CSV_PATH",
     "ROSTER_SPREADSHEET__ID",
     "ROSTER_SPREADSHEET__RANGE",
+    "ROSTER_SYNC__NIGHTLY_ROLES",
     "SERVICE_ACCOUNT_KEY__ID",
     "SERVICE_ACCOUNT_KEY__EMAIL",
     "SERVICE_ACCOUNT_KEY__PEM",
@@ -72,6 +81,8 @@ struct AppVarsInner {
     google_service_account: Arc<RwLock<GoogleServiceAccount>>,
     roster: RwLock<Roster>,
     http: HttpVars,
+    timezone: chrono_tz::Tz,
+    nightly_role_sync: bool,
 }
 
 #[derive(Clone)]
@@ -100,15 +111,27 @@ impl AppVars {
 
         Self {
             inner: std::sync::Arc::new(AppVarsInner {
-                db: connection,
                 channels: ChannelVars::new(&env),
                 http: HttpVars::new(&env),
                 roles: RoleVars::new(&env),
                 roster: RwLock::new(Roster::new(
-                    &env.roster_spreadsheet,
-                    google_service_account.clone(),
+                    roster_source_from_env(
+                        &env,
+                        google_service_account.clone(),
+                        connection.clone(),
+                    )
+                    .expect("ROSTER_SOURCE__KIND must be sheet, csv or db"),
+                    connection.clone(),
                 )),
                 google_service_account,
+                db: connection,
+                timezone: env
+                    .app
+                    .timezone
+                    .parse()
+                    .expect("APP__TIMEZONE must be an IANA timezone name"),
+                nightly_role_sync: parse_flag(&env.roster_sync.nightly_roles)
+                    .expect("ROSTER_SYNC__NIGHTLY_ROLES must be true or false"),
                 env,
             }),
         }
@@ -57,6 +57,17 @@ pub fn graph_pair<T: Hash + Eq + Copy>(
     vec: Vec<T>,
     previous_pairings: &[Match<T>],
     seed: u64,
+) -> Result<Pairing<T>> {
+    graph_pair_by(vec, previous_pairings, seed, |_, _| true)
+}
+
+/// Like `graph_pair`, but elements that are not `compatible` are avoided the same way as elements
+/// that were previously paired.
+pub fn graph_pair_by<T: Hash + Eq + Copy, F: Fn(&T, &T) -> bool>(
+    vec: Vec<T>,
+    previous_pairings: &[Match<T>],
+    seed: u64,
+    compatible: F,
 ) -> Result<Pairing<T>> {
     if vec.len() < 2 {
         bail!("Cannot pair with < 2 elements.");
@@ -68,7 +79,7 @@ pub fn graph_pair<T: Hash + Eq + Copy>(
     }
     let vec = shuffled(vec, seed);
 
-    let (graph, constraints) = build_matching_graph(&vec, previous_pairings);
+    let (graph, constraints) = build_matching_graph(&vec, previous_pairings, compatible);
     let matching = maximum_matching(&graph);
 
     let matched: Vec<Match<NodeId>> = matching
@@ -113,9 +124,10 @@ pub fn graph_pair<T: Hash + Eq + Copy>(
     Ok(Pairing(matched_with_remainder, imperfect_matches))
 }
 
-fn build_matching_graph<T: Hash + Eq + Copy>(
+fn build_matching_graph<T: Hash + Eq + Copy, F: Fn(&T, &T) -> bool>(
     vec: &[T],
     previous_pairings: &[Match<T>],
+    compatible: F,
 ) -> (UnMatrix, HashSet<ConstraintEdge>) {
     let nodes: HashMap<&T, NodeId> = vec
         .iter()
@@ -140,6 +152,13 @@ fn build_matching_graph<T: Hash + Eq + Copy>(
                 .tuple_combinations()
                 .map(ConstraintEdge::new)
         })
+        .chain(
+            nodes
+                .iter()
+                .tuple_combinations()
+                .filter(|((a, _), (b, _))| !compatible(a, b))
+                .map(|((_, a), (_, b))| ConstraintEdge::new((*a, *b))),
+        )
         .collect();
 
     (
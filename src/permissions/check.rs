@@ -0,0 +1,77 @@
+// Limits commands to roster committees, as configured with /permissions
+
+use anyhow::{Context as _, bail};
+use entity::command_permission;
+use itertools::Itertools as _;
+use poise::BoxFuture;
+use sea_orm::{ColumnTrait as _, EntityTrait as _, QueryFilter as _};
+use serenity::FutureExt as _;
+
+use crate::{AppContext, AppError};
+
+/// A command's qualified name followed by each of its parent groups, e.g. "bnb manage create",
+/// "bnb manage", "bnb"
+pub(crate) fn command_and_parents(qualified_name: &str) -> Vec<String> {
+    let words = qualified_name.split(' ').collect_vec();
+    (1..=words.len())
+        .rev()
+        .map(|len| words[..len].join(" "))
+        .collect_vec()
+}
+
+async fn is_allowed(ctx: AppContext<'_>) -> Result<bool, AppError> {
+    let name = &ctx.command().qualified_name;
+    // never let a bad rule lock admins out of fixing it
+    if command_and_parents(name).last().map(String::as_str) == Some("permissions") {
+        return Ok(true);
+    }
+
+    let names = command_and_parents(name);
+    let rules = command_permission::Entity::find()
+        .filter(command_permission::Column::Command.is_in(names.clone()))
+        .all(&ctx.data().db)
+        .await
+        .context("fetch command permissions")?;
+
+    // the most specific command with rules decides, so a subcommand can be opened up or locked
+    // down more than its group
+    let Some(level) = names
+        .iter()
+        .find(|name| rules.iter().any(|rule| &rule.command == *name))
+    else {
+        return Ok(true);
+    };
+    let committees = rules
+        .iter()
+        .filter(|rule| &rule.command == level)
+        .map(|rule| rule.committee.as_str())
+        .sorted()
+        .collect_vec();
+
+    let member = ctx
+        .data()
+        .roster
+        .write()
+        .await
+        .get_user_from_discord(ctx.author(), false)
+        .await?;
+    if member.is_some_and(|member| {
+        member
+            .committees
+            .iter()
+            .any(|committee| committees.contains(&committee.as_str()))
+    }) {
+        return Ok(true);
+    }
+
+    bail!(
+        "`/{name}` is limited to members of {}",
+        committees.iter().map(|name| format!("`{name}`")).join(", ")
+    )
+}
+
+pub(crate) fn check_command_invocation(
+    ctx: AppContext<'_>,
+) -> BoxFuture<'_, Result<bool, AppError>> {
+    is_allowed(ctx).boxed()
+}
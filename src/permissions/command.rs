@@ -0,0 +1,213 @@
+// subcommands: allow, revoke, list
+
+use anyhow::Context as _;
+use entity::command_permission;
+use itertools::Itertools as _;
+use poise::Command;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, EntityTrait as _, QueryFilter as _, QueryOrder as _,
+    sea_query::OnConflict,
+};
+use serenity::all::{CreateEmbed, Mentionable as _, UserId};
+use std::num::NonZeroUsize;
+
+use crate::{
+    AppContext, AppError, AppVars,
+    util::{
+        ContextExtras as _,
+        paginate::{EmbedLinePaginator, PaginatorOptions},
+    },
+};
+
+/// Committee names are compared the way the roster stores them
+fn normalize_committee(committee: &str) -> String {
+    committee.trim().to_lowercase().replace('_', "")
+}
+
+fn qualified_names(commands: &[Command<AppVars, AppError>], names: &mut Vec<String>) {
+    for command in commands {
+        names.push(command.qualified_name.clone());
+        qualified_names(&command.subcommands, names);
+    }
+}
+
+fn command_names(ctx: AppContext<'_>) -> Vec<String> {
+    let mut names = Vec::new();
+    qualified_names(&ctx.framework().options().commands, &mut names);
+    names.sort();
+    names
+}
+
+// poise requires autocomplete callbacks to be async
+#[expect(clippy::unused_async)]
+async fn command_name_choices(ctx: AppContext<'_>, partial: &str) -> Vec<String> {
+    let partial = partial.trim().trim_start_matches('/').to_lowercase();
+    command_names(ctx)
+        .into_iter()
+        .filter(|name| name.contains(&partial))
+        .take(25)
+        .collect_vec()
+}
+
+/// Checks the command exists, returning its name without a leading slash
+fn expect_command(ctx: AppContext<'_>, command: &str) -> anyhow::Result<String> {
+    let command = command.trim().trim_start_matches('/').to_owned();
+    anyhow::ensure!(
+        command_names(ctx).contains(&command),
+        "There is no command named `/{command}`"
+    );
+    Ok(command)
+}
+
+/// Limit bot commands to roster committees
+#[poise::command(
+    slash_command,
+    subcommands("allow", "revoke", "list"),
+    required_permissions = "ADMINISTRATOR",
+    guild_only
+)]
+pub(crate) async fn permissions(ctx: AppContext<'_>) -> Result<(), AppError> {
+    ctx.reply("base command is a noop").await?;
+    Ok(())
+}
+
+/// Let a committee use a command; once a command has a committee, only those committees can use it
+#[poise::command(slash_command, ephemeral)]
+async fn allow(
+    ctx: AppContext<'_>,
+    #[description = "Command or command group, e.g. shortlink or bnb manage"]
+    #[autocomplete = "command_name_choices"]
+    command: String,
+    #[description = "Committee as written on the roster, e.g. board"] committee: String,
+) -> Result<(), AppError> {
+    let command = expect_command(ctx, &command)?;
+    let committee = normalize_committee(&committee);
+    anyhow::ensure!(!committee.is_empty(), "Committee can't be empty");
+
+    command_permission::Entity::insert(command_permission::ActiveModel {
+        command: ActiveValue::Set(command.clone()),
+        committee: ActiveValue::Set(committee.clone()),
+        added_by: ActiveValue::Set(ctx.author().id.get() as i64),
+        ..Default::default()
+    })
+    .on_conflict(
+        OnConflict::columns([
+            command_permission::Column::Command,
+            command_permission::Column::Committee,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .exec_without_returning(&ctx.data().db)
+    .await
+    .context("save command permission")?;
+
+    ctx.reply_ephemeral(format!(
+        "`{committee}` can now use `/{command}` and its subcommands"
+    ))
+    .await?;
+    Ok(())
+}
+
+/// Stop letting a committee use a command; a command with no committees left is open again
+#[poise::command(slash_command, ephemeral)]
+async fn revoke(
+    ctx: AppContext<'_>,
+    #[description = "Command or command group"]
+    #[autocomplete = "command_name_choices"]
+    command: String,
+    #[description = "Committee to remove"] committee: String,
+) -> Result<(), AppError> {
+    let command = command.trim().trim_start_matches('/').to_owned();
+    let committee = normalize_committee(&committee);
+
+    let deleted = command_permission::Entity::delete_many()
+        .filter(command_permission::Column::Command.eq(&command))
+        .filter(command_permission::Column::Committee.eq(&committee))
+        .exec(&ctx.data().db)
+        .await
+        .context("delete command permission")?;
+
+    let remaining = command_permission::Entity::find()
+        .filter(command_permission::Column::Command.eq(&command))
+        .all(&ctx.data().db)
+        .await
+        .context("fetch command permissions")?;
+
+    let text = match (deleted.rows_affected, remaining.is_empty()) {
+        (0, _) => format!("`{committee}` wasn't allowed to use `/{command}`"),
+        (_, true) => format!(
+            "`{committee}` was removed, and `/{command}` no longer has committee restrictions"
+        ),
+        (_, false) => format!(
+            "`{committee}` was removed; `/{command}` is still limited to {}",
+            remaining
+                .iter()
+                .map(|rule| format!("`{}`", rule.committee))
+                .join(", ")
+        ),
+    };
+    ctx.reply_ephemeral(text).await?;
+    Ok(())
+}
+
+/// Show which committees can use each restricted command
+#[poise::command(slash_command, ephemeral)]
+async fn list(ctx: AppContext<'_>) -> Result<(), AppError> {
+    let rules = command_permission::Entity::find()
+        .order_by_asc(command_permission::Column::Command)
+        .order_by_asc(command_permission::Column::Committee)
+        .all(&ctx.data().db)
+        .await
+        .context("fetch command permissions")?;
+
+    if rules.is_empty() {
+        ctx.reply_ephemeral(
+            "No commands are limited to committees. Add a rule with `/permissions allow`",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let lines = rules
+        .iter()
+        .chunk_by(|rule| &rule.command)
+        .into_iter()
+        .map(|(command, rules)| {
+            let rules = rules.collect_vec();
+            format!(
+                "`/{command}`: {} (added by {})",
+                rules
+                    .iter()
+                    .map(|rule| format!("`{}`", rule.committee))
+                    .join(", "),
+                rules
+                    .iter()
+                    .map(|rule| UserId::new(rule.added_by as u64))
+                    .unique()
+                    .map(|id| id.mention().to_string())
+                    .join(", ")
+            )
+            .into_boxed_str()
+        })
+        .collect_vec();
+
+    let embed = CreateEmbed::default()
+        .color(0xff87a6)
+        .title("Command Permissions")
+        .description("Commands not listed are only limited by Discord's integration settings.");
+    let paginator = EmbedLinePaginator::new(
+        lines,
+        PaginatorOptions::default()
+            .sep("\n".into())
+            .max_lines(NonZeroUsize::new(15).unwrap())
+            .ephemeral(true)
+            .embed(embed),
+    );
+
+    paginator
+        .run(ctx)
+        .await
+        .context("command permissions paginate")?;
+    Ok(())
+}
@@ -0,0 +1,513 @@
+// /roster offboard and /roster onboard: everything board does by hand when someone leaves or
+// joins ICSSC, behind one confirmation
+
+use anyhow::{Context as _, bail};
+use chrono::Utc;
+use entity::{matchy_meetup_opt_in, role_sync_change, roster_link};
+use itertools::Itertools as _;
+use poise::CreateReply;
+use sea_orm::{ActiveValue, EntityTrait as _, sea_query::OnConflict};
+use serenity::all::{
+    CreateInteractionResponse, CreateInteractionResponseMessage, GuildId, Mentionable as _, Role,
+    RoleId, User,
+};
+
+use crate::{
+    AppContext, AppError, AppVars,
+    bitsnbytes::fam::fams_of_users,
+    roster::desynced::{committee_roles, ensure_board, is_admin_email, log_sync, role_change},
+    util::{
+        components::{FLOW_TIMEOUT, confirm_buttons_row},
+        gdrive::{
+            DriveFilePermissionRole, create_gdrive_permission, delete_gdrive_permission,
+            get_gdrive_permissions, update_gdrive_permission,
+        },
+    },
+};
+
+/// One change made while onboarding or offboarding someone
+enum Step {
+    AddRole(Role),
+    RemoveRole(Role),
+    GrantDrive {
+        email: String,
+        role: DriveFilePermissionRole,
+    },
+    UpdateDrive {
+        email: String,
+        permission_id: String,
+        from: DriveFilePermissionRole,
+        to: DriveFilePermissionRole,
+    },
+    RevokeDrive {
+        email: String,
+        permission_id: String,
+        role: DriveFilePermissionRole,
+    },
+    Link(String),
+    MatchyOptIn,
+    MatchyOptOut,
+    AddSocials,
+    RemoveSocials,
+}
+
+impl Step {
+    fn describe(&self) -> String {
+        match self {
+            Self::AddRole(role) => format!("Add the `{}` role", role.name),
+            Self::RemoveRole(role) => format!("Remove the `{}` role", role.name),
+            Self::GrantDrive { email, role } => {
+                format!("Add `{email}` to the Shared Drive as `{}`", role.label())
+            }
+            Self::UpdateDrive {
+                email, from, to, ..
+            } => format!(
+                "Change `{email}` from `{}` to `{}` on the Shared Drive",
+                from.label(),
+                to.label()
+            ),
+            Self::RevokeDrive { email, role, .. } => {
+                format!(
+                    "Remove `{email}` (`{}`) from the Shared Drive",
+                    role.label()
+                )
+            }
+            Self::Link(email) => format!("Link their Discord account to `{email}`"),
+            Self::MatchyOptIn => String::from("Opt them in to matchy meetups"),
+            Self::MatchyOptOut => String::from("Opt them out of matchy meetups"),
+            Self::AddSocials => String::from("Add the socials ping role"),
+            Self::RemoveSocials => String::from("Remove the socials ping role"),
+        }
+    }
+
+    async fn run(&self, ctx: AppContext<'_>, guild: GuildId, user: &User) -> anyhow::Result<()> {
+        let data = ctx.data();
+        let http = ctx.http();
+        let socials = RoleId::new(data.roles.socials_role_id);
+
+        match self {
+            Self::AddRole(role) | Self::RemoveRole(role) => {
+                let added = matches!(self, Self::AddRole(_));
+                match added {
+                    true => {
+                        http.add_member_role(guild, user.id, role.id, Some("Roster onboarding"))
+                            .await?;
+                    }
+                    false => {
+                        http.remove_member_role(
+                            guild,
+                            user.id,
+                            role.id,
+                            Some("Roster offboarding"),
+                        )
+                        .await?;
+                    }
+                }
+                role_sync_change::Entity::insert(role_change(
+                    guild,
+                    user.id,
+                    role,
+                    added,
+                    Some(ctx.author().id),
+                ))
+                .exec(&data.db)
+                .await
+                .context("log role change")?;
+            }
+            Self::GrantDrive { email, role } => create_gdrive_permission(data, email, role).await?,
+            Self::UpdateDrive {
+                permission_id, to, ..
+            } => update_gdrive_permission(data, permission_id, to).await?,
+            Self::RevokeDrive { permission_id, .. } => {
+                delete_gdrive_permission(data, permission_id).await?;
+            }
+            Self::Link(email) => {
+                roster_link::Entity::insert(roster_link::ActiveModel {
+                    discord_id: ActiveValue::Set(user.id.get() as i64),
+                    email: ActiveValue::Set(email.to_lowercase()),
+                    linked_at: ActiveValue::Set(Utc::now().naive_utc()),
+                    approved_by: ActiveValue::Set(Some(ctx.author().id.get() as i64)),
+                })
+                .on_conflict(
+                    OnConflict::column(roster_link::Column::DiscordId)
+                        .update_columns([
+                            roster_link::Column::Email,
+                            roster_link::Column::LinkedAt,
+                            roster_link::Column::ApprovedBy,
+                        ])
+                        .to_owned(),
+                )
+                .exec(&data.db)
+                .await
+                .context("save roster link")?;
+                data.roster.write().await.reload_links().await?;
+            }
+            Self::MatchyOptIn => {
+                matchy_meetup_opt_in::Entity::insert(matchy_meetup_opt_in::ActiveModel {
+                    user_id: ActiveValue::Set(user.id.get() as i64),
+                    created_at: ActiveValue::NotSet,
+                })
+                .on_conflict_do_nothing()
+                .exec(&data.db)
+                .await
+                .context("opt in to matchy")?;
+            }
+            Self::MatchyOptOut => {
+                matchy_meetup_opt_in::Entity::delete_by_id(user.id.get() as i64)
+                    .exec(&data.db)
+                    .await
+                    .context("opt out of matchy")?;
+            }
+            Self::AddSocials => {
+                http.add_member_role(guild, user.id, socials, Some("Roster onboarding"))
+                    .await?;
+            }
+            Self::RemoveSocials => {
+                http.remove_member_role(guild, user.id, socials, Some("Roster offboarding"))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Shows the planned steps, and after confirmation runs them and reports anything left to do
+async fn confirm_and_run(
+    ctx: AppContext<'_>,
+    guild: GuildId,
+    user: &User,
+    action: &str,
+    steps: Vec<Step>,
+    mut checklist: Vec<String>,
+) -> Result<(), AppError> {
+    let list = |lines: &[String]| lines.iter().map(|line| format!("- {line}")).join("\n");
+    let checklist_text = |checklist: &[String]| match checklist.is_empty() {
+        true => String::new(),
+        false => format!("\n\n**Still to do by hand**\n{}", list(checklist)),
+    };
+
+    if steps.is_empty() {
+        ctx.send(
+            CreateReply::default()
+                .content(format!(
+                    "There is nothing to {action} for {} automatically.{}",
+                    user.mention(),
+                    checklist_text(&checklist)
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let planned = steps.iter().map(Step::describe).collect_vec();
+    let prefix = action.to_lowercase();
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(format!(
+                    "**{action} {}**\n{}{}",
+                    user.mention(),
+                    list(&planned),
+                    checklist_text(&checklist)
+                ))
+                .components(vec![confirm_buttons_row(&prefix)])
+                .ephemeral(true),
+        )
+        .await?;
+    let sent = handle.message().await?;
+
+    let Some(ixn) = sent
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(FLOW_TIMEOUT)
+        .await
+    else {
+        handle
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .content("Timed out, nothing was changed")
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    if ixn.data.custom_id != format!("{prefix}_confirm") {
+        ixn.create_response(
+            ctx.http(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content("Cancelled, nothing was changed")
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+    ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let mut done = Vec::new();
+    for step in &steps {
+        match step.run(ctx, guild, user).await {
+            Ok(()) => done.push(step.describe()),
+            Err(why) => {
+                dbg!(&why);
+                checklist.push(format!("{} (failed: {why})", step.describe()));
+            }
+        }
+    }
+
+    let summary = format!(
+        "**Done**\n{}{}",
+        match done.is_empty() {
+            true => String::from("- Nothing"),
+            false => list(&done),
+        },
+        checklist_text(&checklist)
+    );
+    log_sync(
+        ctx.serenity_context(),
+        ctx.data(),
+        &format!("{action} {} by {}", user.mention(), ctx.author().mention()),
+        &summary,
+    )
+    .await;
+
+    handle
+        .edit(
+            ctx,
+            CreateReply::default().content(summary).components(vec![]),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Whether the bot is allowed to change this email's Drive access
+fn is_managed_email(data: &AppVars, email: &str) -> bool {
+    !is_admin_email(email) && !email.eq_ignore_ascii_case(&data.env.service_account_key.email)
+}
+
+/// Remove someone's committee roles, Drive access, matchy opt-in and socials role
+#[poise::command(slash_command, hide_in_help, ephemeral)]
+pub(crate) async fn offboard(
+    ctx: AppContext<'_>,
+    #[description = "Member who is leaving ICSSC"] user: User,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    ensure_board(ctx, "Only board members can offboard members").await?;
+    let data = ctx.data();
+    let guild = ctx.guild_id().context("get guild id")?;
+
+    let roster = data.roster.write().await.snapshot(0).await?;
+    let row = roster.find(user.id, &user.name).cloned();
+    let linked_email = roster_link::Entity::find_by_id(user.id.get() as i64)
+        .one(&data.db)
+        .await
+        .context("fetch roster link")?
+        .map(|link| link.email);
+    let email = row
+        .as_ref()
+        .map(|row| row.email.clone())
+        .filter(|email| !email.is_empty())
+        .or(linked_email);
+
+    let mut steps = Vec::new();
+    let mut checklist = Vec::new();
+
+    if let Some(row) = &row {
+        checklist.push(format!(
+            "Remove **{}** (`{}`) from the roster",
+            row.name, row.email
+        ));
+    }
+
+    match guild.member(ctx, user.id).await {
+        Ok(member) => {
+            let role_map = guild.roles(ctx).await?;
+            let committee_roles = committee_roles(&roster.rows, &role_map);
+            steps.extend(
+                member
+                    .roles
+                    .iter()
+                    .filter_map(|id| role_map.get(id))
+                    .filter(|role| {
+                        committee_roles
+                            .get(&role.name)
+                            .is_some_and(|committee_role| committee_role.id == role.id)
+                    })
+                    .map(|role| Step::RemoveRole(role.clone())),
+            );
+            if member
+                .roles
+                .contains(&RoleId::new(data.roles.socials_role_id))
+            {
+                steps.push(Step::RemoveSocials);
+            }
+        }
+        Err(_) => checklist.push(String::from(
+            "They aren't in this server, so check their roles in any other ICSSC servers",
+        )),
+    }
+
+    match &email {
+        Some(email) if !is_managed_email(data, email) => checklist.push(format!(
+            "`{email}` is managed by hand; the bot won't change its Drive access"
+        )),
+        Some(email) => {
+            let permission = get_gdrive_permissions(data)
+                .await
+                .context("Failed to fetch permissions; ensure service account has access")?
+                .into_iter()
+                .find(|permission| permission.email_address.eq_ignore_ascii_case(email));
+            if let Some(permission) = permission {
+                steps.push(Step::RevokeDrive {
+                    email: permission.email_address,
+                    permission_id: permission.id,
+                    role: permission.role,
+                });
+            }
+        }
+        None => checklist.push(String::from(
+            "Their email isn't known, so check Drive access by hand with `/roster check_google_access`",
+        )),
+    }
+
+    let opted_in = matchy_meetup_opt_in::Entity::find_by_id(user.id.get() as i64)
+        .one(&data.db)
+        .await
+        .context("fetch matchy opt in")?;
+    if opted_in.is_some() {
+        steps.push(Step::MatchyOptOut);
+    }
+
+    for fam in fams_of_users(&data.db, &[user.id]).await? {
+        checklist.push(format!(
+            "Remove them from the **{}** fam with `/bnb manage remove` if they're no longer a bit or byte",
+            fam.name
+        ));
+    }
+
+    confirm_and_run(ctx, guild, &user, "Offboard", steps, checklist).await
+}
+
+/// Give a new roster member their committee roles, Drive access, matchy opt-in and socials role
+#[poise::command(slash_command, hide_in_help, ephemeral)]
+pub(crate) async fn onboard(
+    ctx: AppContext<'_>,
+    #[description = "Member who joined ICSSC"] user: User,
+    #[description = "Their roster email, if the roster doesn't have their Discord yet"]
+    email: Option<String>,
+    #[description = "Also opt them in to matchy meetups and socials pings (default: true)"]
+    opt_in: Option<bool>,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    ensure_board(ctx, "Only board members can onboard members").await?;
+    let data = ctx.data();
+    let guild = ctx.guild_id().context("get guild id")?;
+
+    let roster = data.roster.write().await.snapshot(0).await?;
+    let matched = roster.find(user.id, &user.name);
+    let row = match &email {
+        Some(email) => roster
+            .rows
+            .iter()
+            .find(|row| row.email.eq_ignore_ascii_case(email.trim())),
+        None => matched,
+    };
+    let Some(row) = row.cloned() else {
+        bail!(
+            "{} isn't on the roster yet; add them to the roster first, or pass the email they're listed under",
+            user.mention()
+        );
+    };
+    let Ok(member) = guild.member(ctx, user.id).await else {
+        bail!("{} needs to join this server first", user.mention());
+    };
+
+    let mut steps = Vec::new();
+    let mut checklist = Vec::new();
+
+    let already_matched = matched.is_some_and(|it| it.email.eq_ignore_ascii_case(&row.email));
+    if !already_matched {
+        match roster.discord_id_of(&row) {
+            Some(other) if other != user.id => bail!(
+                "`{}` belongs to {} on the roster; unlink them with `/roster unlink` first",
+                row.email,
+                other.mention()
+            ),
+            _ => steps.push(Step::Link(row.email.clone())),
+        }
+    }
+
+    let role_map = guild.roles(ctx).await?;
+    let committee_roles = committee_roles(&roster.rows, &role_map);
+    for committee in row.committees.iter().filter(|name| !name.is_empty()) {
+        match committee_roles.get(committee) {
+            Some(role) if member.roles.contains(&role.id) => {}
+            Some(role) => steps.push(Step::AddRole((*role).clone())),
+            None => checklist.push(format!(
+                "There's no `{committee}` role, so give them their committee role by hand"
+            )),
+        }
+    }
+
+    if row.email.is_empty() {
+        checklist.push(String::from(
+            "Their roster row has no email, so share the Drive with them by hand",
+        ));
+    } else if !is_managed_email(data, &row.email) {
+        checklist.push(format!(
+            "`{}` is managed by hand; the bot won't change its Drive access",
+            row.email
+        ));
+    } else {
+        let permission = get_gdrive_permissions(data)
+            .await
+            .context("Failed to fetch permissions; ensure service account has access")?
+            .into_iter()
+            .find(|permission| permission.email_address.eq_ignore_ascii_case(&row.email));
+        match permission {
+            // board members who were already on the Drive still need to become managers
+            Some(permission)
+                if row.is_board() && permission.role != DriveFilePermissionRole::Organizer =>
+            {
+                steps.push(Step::UpdateDrive {
+                    email: permission.email_address,
+                    permission_id: permission.id,
+                    from: permission.role,
+                    to: DriveFilePermissionRole::Organizer,
+                });
+            }
+            Some(_) => {}
+            None => steps.push(Step::GrantDrive {
+                email: row.email.clone(),
+                role: match row.is_board() {
+                    true => DriveFilePermissionRole::Organizer,
+                    false => DriveFilePermissionRole::Writer,
+                },
+            }),
+        }
+    }
+
+    if opt_in.unwrap_or(true) {
+        if !member
+            .roles
+            .contains(&RoleId::new(data.roles.socials_role_id))
+        {
+            steps.push(Step::AddSocials);
+        }
+        let opted_in = matchy_meetup_opt_in::Entity::find_by_id(user.id.get() as i64)
+            .one(&data.db)
+            .await
+            .context("fetch matchy opt in")?;
+        if opted_in.is_none() {
+            steps.push(Step::MatchyOptIn);
+        }
+    }
+
+    confirm_and_run(ctx, guild, &user, "Onboard", steps, checklist).await
+}
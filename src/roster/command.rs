@@ -1,13 +1,24 @@
 use crate::AppContext;
 use crate::AppError;
+use crate::roster::boarding::{offboard, onboard};
 use crate::roster::desynced::check_discord_roles;
 use crate::roster::desynced::check_google_access;
+use crate::roster::link::{link, unlink, verify};
 use crate::roster::user_lookup::lookup_discord;
 
 #[poise::command(
     prefix_command,
     slash_command,
-    subcommands("check_discord_roles", "check_google_access", "lookup_discord"),
+    subcommands(
+        "check_discord_roles",
+        "check_google_access",
+        "lookup_discord",
+        "link",
+        "verify",
+        "unlink",
+        "onboard",
+        "offboard"
+    ),
     guild_only
 )]
 pub(crate) async fn roster(ctx: AppContext<'_>) -> Result<(), AppError> {
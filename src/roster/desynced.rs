@@ -1,104 +1,492 @@
 use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
 
 use crate::{
-    AppContext, AppError,
+    AppContext, AppError, AppVars,
     util::{
         ContextExtras as _,
-        gdrive::{DriveFilePermissionRole, get_gdrive_permissions},
+        components::{FLOW_TIMEOUT, confirm_buttons_row},
+        gdrive::{
+            DriveFilePermissionRole, create_gdrive_permission, delete_gdrive_permission,
+            get_gdrive_permissions, update_gdrive_permission,
+        },
+        paginate::{EmbedLinePaginator, PaginatorOptions},
         roster::RosterSheetRow,
     },
 };
-use anyhow::Context as _;
+use anyhow::{Context as _, bail, ensure};
+use entity::role_sync_change;
 use itertools::Itertools as _;
-use serenity::{all::Mentionable as _, futures::StreamExt as _};
+use poise::CreateReply;
+use sea_orm::{ActiveValue, EntityTrait as _};
+use serenity::{
+    all::{
+        ChannelId, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage,
+        GuildId, Mentionable as _, Role, RoleId, UserId,
+    },
+    futures::StreamExt as _,
+};
 
-/// Get a list of server members whose roles are out of sync with the roster
-#[poise::command(slash_command, hide_in_help, ephemeral)]
-pub(crate) async fn check_discord_roles(ctx: AppContext<'_>) -> Result<(), AppError> {
-    ctx.defer_ephemeral().await?;
+/// Committee roles a server member is missing or shouldn't have, going by the roster
+struct RoleDiff {
+    user_id: UserId,
+    missing: Vec<Role>,
+    extra: Vec<Role>,
+}
 
-    let roster = ctx.data().roster.write().await.fetch(0).await?.clone();
-    let roster_lookup = roster
+struct RoleSyncPlan {
+    diffs: Vec<RoleDiff>,
+    /// Committees on the roster without a role of the same name, which can't be synced
+    unmatched_committees: Vec<String>,
+}
+
+/// Roles named after a committee on the roster, keyed by committee. If several roles share a
+/// name, the oldest one is used.
+pub(crate) fn committee_roles<'a>(
+    rows: &[RosterSheetRow],
+    role_map: &'a HashMap<RoleId, Role>,
+) -> HashMap<&'a String, &'a Role> {
+    let committee_names = rows
         .iter()
-        .map(|row| (&*row.discord, row))
-        .collect::<HashMap<&str, &RosterSheetRow>>();
+        .flat_map(|member| &member.committees)
+        .filter(|name| !name.is_empty())
+        .collect::<HashSet<&String>>();
+    role_map
+        .values()
+        .filter(|role| committee_names.contains(&role.name))
+        .sorted_by_key(|role| role.id)
+        .unique_by(|role| &role.name)
+        .map(|role| (&role.name, role))
+        .collect()
+}
 
-    let guild = ctx.guild_id().context("get guild id")?;
-    let role_map = guild.roles(ctx.http()).await?;
-    let committee_names = roster
+/// Bails unless the person running the command is on board
+pub(crate) async fn ensure_board(ctx: AppContext<'_>, message: &str) -> anyhow::Result<()> {
+    let is_board = ctx
+        .data()
+        .roster
+        .write()
+        .await
+        .get_user_from_discord(ctx.author(), false)
+        .await?
+        .is_some_and(|row| row.is_board());
+    if !is_board {
+        bail!("{message}");
+    }
+    Ok(())
+}
+
+/// An audit log row for a committee role someone was given or lost
+pub(crate) fn role_change(
+    guild: GuildId,
+    user_id: UserId,
+    role: &Role,
+    added: bool,
+    applied_by: Option<UserId>,
+) -> role_sync_change::ActiveModel {
+    role_sync_change::ActiveModel {
+        guild_id: ActiveValue::Set(guild.get() as i64),
+        discord_id: ActiveValue::Set(user_id.get() as i64),
+        role_id: ActiveValue::Set(role.id.get() as i64),
+        role_name: ActiveValue::Set(role.name.clone()),
+        added: ActiveValue::Set(added),
+        applied_by: ActiveValue::Set(applied_by.map(|id| id.get() as i64)),
+        ..Default::default()
+    }
+}
+
+async fn plan_role_sync(
+    ctx: &serenity::all::Context,
+    data: &AppVars,
+    guild: GuildId,
+) -> anyhow::Result<RoleSyncPlan> {
+    let roster = data.roster.write().await.snapshot(0).await?;
+    // an empty roster would strip every committee role
+    ensure!(
+        !roster.rows.is_empty(),
+        "The roster came back empty, so roles can't be synced"
+    );
+
+    let role_map = guild.roles(ctx).await?;
+    let committee_roles = committee_roles(&roster.rows, &role_map);
+    let unmatched_committees = roster
+        .rows
         .iter()
         .flat_map(|member| &member.committees)
+        .filter(|name| !name.is_empty() && !committee_roles.contains_key(*name))
         .unique()
-        .collect::<HashSet<&String>>();
+        .cloned()
+        .sorted()
+        .collect_vec();
 
-    let mut member_iter = guild.members_iter(&ctx).boxed();
-    let mut desynced = Vec::new();
+    let mut member_iter = guild.members_iter(ctx).boxed();
+    let mut diffs = Vec::new();
 
-    while let Some(guild_member) = member_iter.next().await
-        && desynced.len() < 10
-    {
+    while let Some(guild_member) = member_iter.next().await {
         let guild_member = guild_member?;
-        let roster_committees = match roster_lookup.get(&*guild_member.user.name) {
+        let roster_committees = match roster.find(guild_member.user.id, &guild_member.user.name) {
             Some(roster_member) => &roster_member.committees,
             None => &vec![],
         };
 
-        let gm_role_names = guild_member
-            .roles
+        let missing = roster_committees
             .iter()
-            .filter_map(|role| Some(&role_map.get(role)?.name))
+            .filter_map(|name| committee_roles.get(name))
+            .filter(|role| !guild_member.roles.contains(&role.id))
+            .map(|role| (*role).clone())
             .collect_vec();
 
-        let extra_roles = gm_role_names
+        let extra = guild_member
+            .roles
             .iter()
-            .filter(|&name| committee_names.contains(name) && !roster_committees.contains(name))
+            .filter_map(|role| role_map.get(role))
+            .filter(|role| {
+                committee_roles
+                    .get(&role.name)
+                    .is_some_and(|committee_role| committee_role.id == role.id)
+                    && !roster_committees.contains(&role.name)
+            })
+            .cloned()
             .collect_vec();
 
-        let missing_roles = roster_committees
+        if missing.is_empty() && extra.is_empty() {
+            continue;
+        }
+
+        diffs.push(RoleDiff {
+            user_id: guild_member.user.id,
+            missing,
+            extra,
+        });
+    }
+
+    Ok(RoleSyncPlan {
+        diffs,
+        unmatched_committees,
+    })
+}
+
+fn role_diff_line(diff: &RoleDiff) -> String {
+    let names = |roles: &[Role]| match roles.is_empty() {
+        true => String::from("\u{2013}"),
+        false => roles
             .iter()
-            .filter(|name| !gm_role_names.contains(name))
-            .collect_vec();
+            .map(|role| format!("`{}`", role.name))
+            .join(", "),
+    };
+    format!(
+        "{}: missing {}; unexpected {}",
+        diff.user_id.mention(),
+        names(&diff.missing),
+        names(&diff.extra)
+    )
+}
 
-        if extra_roles.is_empty() && missing_roles.is_empty() {
-            continue;
+/// Adds missing roles and removes unexpected ones, recording each change in the role sync log.
+/// Returns a summary of what changed.
+async fn apply_role_sync(
+    ctx: &serenity::all::Context,
+    data: &AppVars,
+    guild: GuildId,
+    diffs: &[RoleDiff],
+    applied_by: Option<UserId>,
+) -> anyhow::Result<String> {
+    let reason = match applied_by {
+        Some(_) => "Roster role sync",
+        None => "Nightly roster role sync",
+    };
+
+    let mut changes = Vec::new();
+    let mut failed = Vec::new();
+    for diff in diffs {
+        let updates = diff
+            .missing
+            .iter()
+            .map(|role| (role, true))
+            .chain(diff.extra.iter().map(|role| (role, false)));
+        for (role, added) in updates {
+            let result = match added {
+                true => {
+                    ctx.http
+                        .add_member_role(guild, diff.user_id, role.id, Some(reason))
+                        .await
+                }
+                false => {
+                    ctx.http
+                        .remove_member_role(guild, diff.user_id, role.id, Some(reason))
+                        .await
+                }
+            };
+            if let Err(why) = result {
+                dbg!(why);
+                failed.push(format!("{} `{}`", diff.user_id.mention(), role.name));
+                continue;
+            }
+
+            changes.push(role_change(guild, diff.user_id, role, added, applied_by));
         }
+    }
+
+    let added = changes
+        .iter()
+        .filter(|change| change.added == ActiveValue::Set(true))
+        .count();
+    let removed = changes.len() - added;
+    if !changes.is_empty() {
+        role_sync_change::Entity::insert_many(changes)
+            .exec(&data.db)
+            .await
+            .context("log role sync changes")?;
+    }
+
+    let failed_note = match failed.is_empty() {
+        true => String::new(),
+        false => format!("\nCould not update: {}", failed.join(", ")),
+    };
+    Ok(format!(
+        "Added {added} and removed {removed} committee roles across {} members.{failed_note}",
+        diffs.len()
+    ))
+}
+
+/// Posts a sync summary in the bot log channel, cut short to fit in one message
+pub(crate) async fn log_sync(
+    ctx: &serenity::all::Context,
+    data: &AppVars,
+    heading: &str,
+    summary: &str,
+) {
+    let mut content = format!("**{heading}**\n{summary}");
+    if content.chars().count() > 2000 {
+        content = format!("{}\n...", content.chars().take(1990).collect::<String>());
+    }
+    let logged = ChannelId::new(data.channels.bot_log_channel_id)
+        .say(ctx, content)
+        .await;
+    if let Err(why) = logged {
+        dbg!(why);
+    }
+}
+
+/// Get a list of server members whose roles are out of sync with the roster, and optionally fix them
+#[poise::command(slash_command, hide_in_help, ephemeral)]
+pub(crate) async fn check_discord_roles(
+    ctx: AppContext<'_>,
+    #[description = "Board only: add missing committee roles and remove unexpected ones"]
+    apply: Option<bool>,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    let data = ctx.data();
+    let guild = ctx.guild_id().context("get guild id")?;
+    let apply = apply.unwrap_or(false);
+
+    if apply {
+        ensure_board(ctx, "Only board members can sync roles").await?;
+    }
+
+    let plan = plan_role_sync(ctx.serenity_context(), data, guild).await?;
+    let unmatched_note = match plan.unmatched_committees.is_empty() {
+        true => String::new(),
+        false => format!(
+            "\nThese committees have no role of the same name, so they are skipped: {}",
+            plan.unmatched_committees
+                .iter()
+                .map(|name| format!("`{name}`"))
+                .join(", ")
+        ),
+    };
+
+    if plan.diffs.is_empty() {
+        ctx.reply_ephemeral(format!("All users are in sync!{unmatched_note}"))
+            .await?;
+        return Ok(());
+    }
 
-        desynced.push(format!(
-            "1. {} desynced!\n  - Missing: {}\n  - Unexpected: {}",
-            guild_member.mention(),
-            missing_roles.iter().join(", "),
-            extra_roles.iter().join(", "),
-        ));
+    if !apply {
+        let embed = CreateEmbed::default()
+            .color(0xff87a6)
+            .title(format!("{} Members Desynced", plan.diffs.len()))
+            .description(unmatched_note.trim().to_owned());
+        let paginator = EmbedLinePaginator::new(
+            plan.diffs
+                .iter()
+                .map(|diff| role_diff_line(diff).into_boxed_str())
+                .collect_vec(),
+            PaginatorOptions::default()
+                .sep("\n".into())
+                .max_lines(NonZeroUsize::new(15).unwrap())
+                .ephemeral(true)
+                .embed(embed),
+        );
+        paginator.run(ctx).await.context("role sync paginate")?;
+        return Ok(());
     }
 
-    let text = match desynced.len() {
-        0 => String::from("All users are in sync!"),
-        _ => desynced.join("\n"),
+    let (adds, removes) = plan.diffs.iter().fold((0, 0), |(adds, removes), diff| {
+        (adds + diff.missing.len(), removes + diff.extra.len())
+    });
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(format!(
+                    "This will add {adds} and remove {removes} committee roles across {} \
+                    members. Run without `apply` to review each member first.{unmatched_note}",
+                    plan.diffs.len()
+                ))
+                .components(vec![confirm_buttons_row("role_sync")])
+                .ephemeral(true),
+        )
+        .await?;
+    let sent = handle.message().await?;
+
+    let Some(ixn) = sent
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(FLOW_TIMEOUT)
+        .await
+    else {
+        handle
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .content("Timed out, no roles were changed")
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
     };
 
-    ctx.reply_ephemeral(text).await?;
+    if ixn.data.custom_id != "role_sync_confirm" {
+        ixn.create_response(
+            ctx.http(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content("Cancelled, no roles were changed")
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+    ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let summary = apply_role_sync(
+        ctx.serenity_context(),
+        data,
+        guild,
+        &plan.diffs,
+        Some(ctx.author().id),
+    )
+    .await?;
+    log_sync(
+        ctx.serenity_context(),
+        data,
+        &format!("Roster role sync by {}", ctx.author().mention()),
+        &summary,
+    )
+    .await;
 
+    handle
+        .edit(
+            ctx,
+            CreateReply::default().content(summary).components(vec![]),
+        )
+        .await?;
     Ok(())
 }
 
-const ICSSC_EMAIL: &str = "icssc@uci.edu";
+/// Syncs committee roles in the ICSSC server without confirmation, posting a summary in the bot
+/// log channel if anything changed. Returns how many members were updated.
+pub(crate) async fn sync_discord_roles_nightly(
+    ctx: &serenity::all::Context,
+    data: &AppVars,
+) -> anyhow::Result<usize> {
+    let guild = GuildId::new(data.channels.icssc_guild_id);
+    let plan = plan_role_sync(ctx, data, guild).await?;
+    if plan.diffs.is_empty() {
+        return Ok(0);
+    }
+
+    let summary = apply_role_sync(ctx, data, guild, &plan.diffs, None).await?;
+    log_sync(ctx, data, "Nightly roster role sync", &summary).await;
+    Ok(plan.diffs.len())
+}
+
+pub(crate) const ICSSC_EMAIL: &str = "icssc@uci.edu";
 
 // in case we add more emails, e.g. club advisor, later
-fn is_admin_email(email: &str) -> bool {
-    email == ICSSC_EMAIL
+pub(crate) fn is_admin_email(email: &str) -> bool {
+    email.eq_ignore_ascii_case(ICSSC_EMAIL)
 }
 
-/// Check whether Google Drive access is desynced from the roster
-#[poise::command(slash_command, hide_in_help, ephemeral)]
-pub(crate) async fn check_google_access(ctx: AppContext<'_>) -> Result<(), AppError> {
-    ctx.defer_ephemeral().await?;
-    let data = ctx.data();
+/// A change that brings someone's Drive access in line with the roster
+enum DriveFix {
+    Add {
+        email: String,
+        role: DriveFilePermissionRole,
+    },
+    Update {
+        email: String,
+        permission_id: String,
+        from: DriveFilePermissionRole,
+        to: DriveFilePermissionRole,
+    },
+    Remove {
+        email: String,
+        permission_id: String,
+        from: DriveFilePermissionRole,
+    },
+}
+
+impl DriveFix {
+    fn describe(&self) -> String {
+        match self {
+            Self::Add { email, role } => format!("add `{email}` as `{}`", role.label()),
+            Self::Update {
+                email, from, to, ..
+            } => format!(
+                "change `{email}` from `{}` to `{}`",
+                from.label(),
+                to.label()
+            ),
+            Self::Remove { email, from, .. } => {
+                format!("remove `{email}` (was `{}`)", from.label())
+            }
+        }
+    }
+
+    async fn apply(&self, data: &AppVars) -> Result<(), AppError> {
+        match self {
+            Self::Add { email, role } => create_gdrive_permission(data, email, role).await,
+            Self::Update {
+                permission_id, to, ..
+            } => update_gdrive_permission(data, permission_id, to).await,
+            Self::Remove { permission_id, .. } => {
+                delete_gdrive_permission(data, permission_id).await
+            }
+        }
+    }
+}
+
+/// A Drive permission that doesn't match the roster, and the fix if one can be applied
+struct DriveDesync {
+    line: String,
+    fix: Option<DriveFix>,
+}
 
-    let roster = ctx.data().roster.write().await.fetch(0).await?.clone();
+async fn plan_drive_sync(data: &AppVars) -> anyhow::Result<Vec<DriveDesync>> {
+    let roster = data.roster.write().await.fetch(0).await?.clone();
+    // an empty roster would revoke everyone's access
+    ensure!(
+        !roster.is_empty(),
+        "The roster came back empty, so Drive access can't be checked"
+    );
+    // Drive and the sheet don't agree on casing, so emails are compared lowercased
     let roster_lookup = roster
         .iter()
-        .map(|row| (&*row.email, row))
-        .collect::<HashMap<&str, &RosterSheetRow>>();
+        .map(|row| (row.email.to_lowercase(), row))
+        .collect::<HashMap<String, &RosterSheetRow>>();
 
     let drive_permissions = get_gdrive_permissions(data)
         .await
@@ -106,7 +494,7 @@ pub(crate) async fn check_google_access(ctx: AppContext<'_>) -> Result<(), AppEr
 
     anyhow::ensure!(
         drive_permissions.iter().any(|u| {
-            u.email_address == ICSSC_EMAIL && matches!(u.role, DriveFilePermissionRole::Organizer)
+            is_admin_email(&u.email_address) && matches!(u.role, DriveFilePermissionRole::Organizer)
         }),
         "expected {ICSSC_EMAIL} to have organizer access"
     );
@@ -115,71 +503,226 @@ pub(crate) async fn check_google_access(ctx: AppContext<'_>) -> Result<(), AppEr
 
     // ensure no one on the roster is missing from the drive_permissions list
     // insufficient permissions are handled when iterating the drive_permissions list, not here
-    let mut roster_iter = roster.iter();
     let emails_with_access = drive_permissions
         .iter()
-        .map(|u| u.email_address.as_str())
-        .collect::<HashSet<&str>>();
-
-    while let Some(roster_user) = roster_iter.next()
-        && desynced.len() < 20
-    {
-        let expected = match roster_user.is_board() {
-            true => "`Manager`",
-            false => "`Editor` or `Content Manager`",
-        };
-        if !emails_with_access.contains(roster_user.email.as_str()) {
-            desynced.push(format!(
-                "1. Missing: `{}` is not {}",
-                &roster_user.email, expected
-            ));
+        .map(|u| u.email_address.to_lowercase())
+        .collect::<HashSet<String>>();
+
+    for roster_user in &roster {
+        if roster_user.email.is_empty()
+            || emails_with_access.contains(&roster_user.email.to_lowercase())
+        {
+            continue;
         }
+
+        let (expected, role) = match roster_user.is_board() {
+            true => ("`Manager`", DriveFilePermissionRole::Organizer),
+            false => (
+                "`Editor` or `Content Manager`",
+                DriveFilePermissionRole::Writer,
+            ),
+        };
+        desynced.push(DriveDesync {
+            line: format!("1. Missing: `{}` is not {}", &roster_user.email, expected),
+            fix: (!is_admin_email(&roster_user.email)).then(|| DriveFix::Add {
+                email: roster_user.email.clone(),
+                role,
+            }),
+        });
     }
 
     // ensure that all drive_permissions are found in the roster and are consistent with committee.
-    let mut perms_iter = drive_permissions.iter();
-    'user_with_perms: while let Some(google_user) = perms_iter.next()
-        && desynced.len() < 20
-    {
+    for google_user in &drive_permissions {
         let email = &*google_user.email_address;
-        if email == data.env.service_account_key.email {
+        if email.eq_ignore_ascii_case(&data.env.service_account_key.email) {
             continue;
         }
 
-        let error = match roster_lookup.get(email) {
+        let update = |to: DriveFilePermissionRole| {
+            Some(DriveFix::Update {
+                email: email.to_owned(),
+                permission_id: google_user.id.clone(),
+                from: google_user.role.clone(),
+                to,
+            })
+        };
+
+        let (line, fix) = match roster_lookup.get(&email.to_lowercase()) {
+            // owners can't be changed through the API, and admin accounts are managed by hand
+            _ if is_admin_email(email) => match &google_user.role {
+                DriveFilePermissionRole::Organizer => continue,
+                _ => (format!("1. Insufficient: `{email}` is not `Manager`"), None),
+            },
             Some(val) => match val.is_board() {
                 true => match &google_user.role {
-                    DriveFilePermissionRole::Organizer => continue 'user_with_perms,
-                    _ => format!("1. Insufficient: `{email}` is not `Manager`"),
+                    DriveFilePermissionRole::Organizer => continue,
+                    DriveFilePermissionRole::Owner => {
+                        (format!("1. Insufficient: `{email}` is not `Manager`"), None)
+                    }
+                    _ => (
+                        format!("1. Insufficient: `{email}` is not `Manager`"),
+                        update(DriveFilePermissionRole::Organizer),
+                    ),
                 },
                 false => match &google_user.role {
                     DriveFilePermissionRole::FileOrganizer | DriveFilePermissionRole::Writer => {
-                        continue 'user_with_perms;
+                        continue;
                     }
-                    DriveFilePermissionRole::Organizer => format!(
-                        "1. Unexpected: `{email}` should be `Editor` or `Content Manager`, not `Manager`",
+                    DriveFilePermissionRole::Organizer => (
+                        format!(
+                            "1. Unexpected: `{email}` should be `Editor` or `Content Manager`, not `Manager`",
+                        ),
+                        update(DriveFilePermissionRole::FileOrganizer),
+                    ),
+                    DriveFilePermissionRole::Owner => (
+                        format!(
+                            "1. Unexpected: `{email}` should be `Editor` or `Content Manager`, not `Owner`"
+                        ),
+                        None,
+                    ),
+                    _ => (
+                        format!("1. Insufficient: `{email}` is not `Editor` or `Content Manager`"),
+                        update(DriveFilePermissionRole::Writer),
                     ),
-                    _ => {
-                        format!("1. Insufficient: `{email}` is not `Editor` or `Content Manager`")
-                    }
                 },
             },
-            None if is_admin_email(email) => match &google_user.role {
-                DriveFilePermissionRole::Organizer => continue 'user_with_perms,
-                _ => format!("1. Insufficient: `{email}` is not `Manager`"),
-            },
-            None => format!("1. Unexpected: `{email}`"),
+            None => (
+                format!("1. Unexpected: `{email}`"),
+                (google_user.role != DriveFilePermissionRole::Owner).then(|| DriveFix::Remove {
+                    email: email.to_owned(),
+                    permission_id: google_user.id.clone(),
+                    from: google_user.role.clone(),
+                }),
+            ),
         };
 
-        desynced.push(error);
+        desynced.push(DriveDesync { line, fix });
+    }
+
+    Ok(desynced)
+}
+
+/// Check whether Google Drive access is desynced from the roster, and optionally fix it
+#[poise::command(slash_command, hide_in_help, ephemeral)]
+pub(crate) async fn check_google_access(
+    ctx: AppContext<'_>,
+    #[description = "Board only: add, change or remove Drive access to match the roster"]
+    apply: Option<bool>,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    let data = ctx.data();
+    let apply = apply.unwrap_or(false);
+
+    if apply {
+        ensure_board(ctx, "Only board members can sync Drive access").await?;
     }
 
-    let text = match desynced.len() {
-        0 => String::from("All Google Drive users are in sync!"),
-        _ => desynced.join("\n"),
+    let desynced = plan_drive_sync(data).await?;
+    if desynced.is_empty() {
+        ctx.reply_ephemeral("All Google Drive users are in sync!")
+            .await?;
+        return Ok(());
+    }
+
+    let mut text = desynced.iter().take(20).map(|it| &it.line).join("\n");
+    if desynced.len() > 20 {
+        text = format!("{text}\n...and {} more", desynced.len() - 20);
+    }
+
+    let fixes = desynced
+        .iter()
+        .filter_map(|it| it.fix.as_ref())
+        .collect_vec();
+    if !apply || fixes.is_empty() {
+        ctx.reply_ephemeral(text).await?;
+        return Ok(());
+    }
+
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(format!(
+                    "{text}\n\nThis will make {} Drive permission changes. Anything involving \
+                    `{ICSSC_EMAIL}` or a file owner has to be fixed by hand.",
+                    fixes.len()
+                ))
+                .components(vec![confirm_buttons_row("drive_sync")])
+                .ephemeral(true),
+        )
+        .await?;
+    let sent = handle.message().await?;
+
+    let Some(ixn) = sent
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(FLOW_TIMEOUT)
+        .await
+    else {
+        handle
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .content("Timed out, Drive access was not changed")
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
     };
 
-    ctx.reply_ephemeral(text).await?;
+    if ixn.data.custom_id != "drive_sync_confirm" {
+        ixn.create_response(
+            ctx.http(),
+            CreateInteractionResponse::UpdateMessage(
+                CreateInteractionResponseMessage::new()
+                    .content("Cancelled, Drive access was not changed")
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+    ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let mut applied = Vec::new();
+    let mut failed = Vec::new();
+    for fix in fixes {
+        match fix.apply(data).await {
+            Ok(()) => applied.push(format!("- {}", fix.describe())),
+            Err(why) => {
+                dbg!(why);
+                failed.push(format!("- {} (failed)", fix.describe()));
+            }
+        }
+    }
 
+    let summary = format!(
+        "Made {} of {} Drive permission changes.\n{}",
+        applied.len(),
+        applied.len() + failed.len(),
+        applied.iter().chain(&failed).join("\n")
+    );
+    log_sync(
+        ctx.serenity_context(),
+        data,
+        &format!("Drive permission sync by {}", ctx.author().mention()),
+        &summary,
+    )
+    .await;
+
+    let reply = match summary.chars().count() > 2000 {
+        true => format!(
+            "Made {} of {} Drive permission changes; see the bot log channel for details.",
+            applied.len(),
+            applied.len() + failed.len()
+        ),
+        false => summary,
+    };
+    handle
+        .edit(
+            ctx,
+            CreateReply::default().content(reply).components(vec![]),
+        )
+        .await?;
     Ok(())
 }
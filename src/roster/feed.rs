@@ -0,0 +1,314 @@
+// Posts what changed on the roster since the last time it was saved, so board notices when
+// members join, leave or move committees
+
+use anyhow::Context as _;
+use chrono::Utc;
+use entity::{roster_snapshot, roster_snapshot_row};
+use itertools::Itertools as _;
+use sea_orm::{
+    ActiveValue, EntityTrait as _, ModelTrait as _, QueryOrder as _, TransactionTrait as _,
+};
+use serenity::all::{ChannelId, UserId};
+use std::collections::HashMap;
+
+use crate::{AppVars, util::roster::RosterSheetRow};
+
+/// How stale the roster can be when checking for changes
+const FEED_MAX_AGE_MINS: i64 = 30;
+
+fn committee_list(committees: &[&String]) -> String {
+    committees
+        .iter()
+        .filter(|name| !name.is_empty())
+        .map(|name| format!("`{name}`"))
+        .join(", ")
+}
+
+/// Lines describing what changed about someone who is on both rosters
+fn member_changes(old: &RosterSheetRow, new: &RosterSheetRow) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if old.name != new.name {
+        lines.push(format!("**{}** is now **{}**", old.name, new.name));
+    }
+    if !old.email.eq_ignore_ascii_case(&new.email) {
+        lines.push(format!(
+            "**{}** changed email: `{}` \u{2192} `{}`",
+            new.name, old.email, new.email
+        ));
+    }
+    if !old.discord.eq_ignore_ascii_case(&new.discord) || old.discord_id != new.discord_id {
+        let describe = |row: &RosterSheetRow| match row.discord_id {
+            Some(id) => format!("`{}` ({id})", row.discord),
+            None => format!("`{}`", row.discord),
+        };
+        lines.push(format!(
+            "**{}** changed Discord: {} \u{2192} {}",
+            new.name,
+            describe(old),
+            describe(new)
+        ));
+    }
+
+    let joined = new
+        .committees
+        .iter()
+        .filter(|name| !old.committees.contains(name))
+        .collect_vec();
+    let left = old
+        .committees
+        .iter()
+        .filter(|name| !new.committees.contains(name))
+        .collect_vec();
+    let committee_changes = [("joined", joined), ("left", left)]
+        .into_iter()
+        .filter_map(|(verb, committees)| {
+            let list = committee_list(&committees);
+            (!list.is_empty()).then(|| format!("{verb} {list}"))
+        })
+        .join("; ");
+    if !committee_changes.is_empty() {
+        lines.push(format!("**{}** {committee_changes}", new.name));
+    }
+
+    lines
+}
+
+/// Lines describing the difference between two rosters. Rows are matched by email, then by
+/// Discord user ID or name so that someone changing their email isn't reported as leaving.
+fn diff_rosters(old: &[RosterSheetRow], new: &[RosterSheetRow]) -> Vec<String> {
+    let key = |row: &RosterSheetRow| row.email.to_lowercase();
+    let old_lookup = old
+        .iter()
+        .map(|row| (key(row), row))
+        .collect::<HashMap<_, _>>();
+    let new_lookup = new
+        .iter()
+        .map(|row| (key(row), row))
+        .collect::<HashMap<_, _>>();
+
+    let mut left = old
+        .iter()
+        .filter(|row| !new_lookup.contains_key(&key(row)))
+        .collect_vec();
+    let mut lines = Vec::new();
+    let mut joined = Vec::new();
+
+    for row in new {
+        if let Some(old_row) = old_lookup.get(&key(row)) {
+            lines.extend(member_changes(old_row, row));
+            continue;
+        }
+
+        let same_person = |old_row: &&RosterSheetRow| {
+            (row.discord_id.is_some() && old_row.discord_id == row.discord_id)
+                || old_row.name.eq_ignore_ascii_case(&row.name)
+        };
+        match left.iter().position(same_person) {
+            Some(i) => lines.extend(member_changes(left.remove(i), row)),
+            None => joined.push(row),
+        }
+    }
+
+    let joined = joined.into_iter().map(|row| {
+        format!(
+            "Joined: **{}** (`{}`) {}",
+            row.name,
+            row.email,
+            committee_list(&row.committees.iter().collect_vec())
+        )
+        .trim_end()
+        .to_owned()
+    });
+    let left = left
+        .into_iter()
+        .map(|row| format!("Left: **{}** (`{}`)", row.name, row.email));
+
+    joined.chain(left).chain(lines).collect_vec()
+}
+
+fn row_from_model(row: roster_snapshot_row::Model) -> RosterSheetRow {
+    RosterSheetRow {
+        name: row.name,
+        email: row.email,
+        discord: row.discord,
+        committees: row.committees.split(", ").map(String::from).collect_vec(),
+        discord_id: row.discord_id.map(|id| UserId::new(id as u64)),
+    }
+}
+
+async fn save_snapshot(data: &AppVars, rows: &[RosterSheetRow]) -> anyhow::Result<()> {
+    let txn = data.db.begin().await?;
+    let snapshot = roster_snapshot::Entity::insert(roster_snapshot::ActiveModel {
+        taken_at: ActiveValue::Set(Utc::now().naive_utc()),
+        ..Default::default()
+    })
+    .exec(&txn)
+    .await
+    .context("save roster snapshot")?;
+
+    let models = rows
+        .iter()
+        .map(|row| roster_snapshot_row::ActiveModel {
+            snapshot_id: ActiveValue::Set(snapshot.last_insert_id),
+            name: ActiveValue::Set(row.name.clone()),
+            email: ActiveValue::Set(row.email.clone()),
+            discord: ActiveValue::Set(row.discord.clone()),
+            committees: ActiveValue::Set(row.committees.join(", ")),
+            discord_id: ActiveValue::Set(row.discord_id.map(|id| id.get() as i64)),
+            ..Default::default()
+        })
+        .collect_vec();
+    roster_snapshot_row::Entity::insert_many(models)
+        .exec(&txn)
+        .await
+        .context("save roster snapshot rows")?;
+
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Compares the roster with the last saved snapshot, posting the changes in the bot log channel
+/// and then saving it if anything is different. Returns how many changes were posted.
+pub(crate) async fn post_roster_changes(
+    ctx: &serenity::all::Context,
+    data: &AppVars,
+) -> anyhow::Result<usize> {
+    let rows = data
+        .roster
+        .write()
+        .await
+        .fetch(FEED_MAX_AGE_MINS)
+        .await?
+        .clone();
+    // a failed or truncated read shouldn't be reported as everyone leaving
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let latest = roster_snapshot::Entity::find()
+        .order_by_desc(roster_snapshot::Column::Id)
+        .one(&data.db)
+        .await
+        .context("fetch latest roster snapshot")?;
+    let Some(latest) = latest else {
+        // nothing to compare the first snapshot against
+        save_snapshot(data, &rows).await?;
+        return Ok(0);
+    };
+
+    let previous = latest
+        .find_related(roster_snapshot_row::Entity)
+        .all(&data.db)
+        .await
+        .context("fetch roster snapshot rows")?
+        .into_iter()
+        .map(row_from_model)
+        .collect_vec();
+    let changes = diff_rosters(&previous, &rows);
+    if changes.is_empty() {
+        return Ok(0);
+    }
+
+    let channel = ChannelId::new(data.channels.bot_log_channel_id);
+    let mut message = String::from("**Roster changes**");
+    for line in &changes {
+        if message.len() + line.len() + 1 > 2000 {
+            channel.say(ctx, &message).await?;
+            message.clear();
+        }
+        message = format!("{message}\n{line}");
+    }
+    channel.say(ctx, message.trim_start()).await?;
+
+    // only save once posted, so changes are tried again next time if posting fails
+    save_snapshot(data, &rows).await?;
+
+    Ok(changes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff_rosters;
+    use crate::util::roster::RosterSheetRow;
+    use serenity::all::UserId;
+
+    fn row(
+        name: &str,
+        email: &str,
+        committees: &[&str],
+        discord_id: Option<u64>,
+    ) -> RosterSheetRow {
+        RosterSheetRow {
+            name: name.to_owned(),
+            email: email.to_owned(),
+            discord: name.to_lowercase(),
+            committees: committees.iter().map(|c| (*c).to_owned()).collect(),
+            discord_id: discord_id.map(UserId::new),
+        }
+    }
+
+    #[test]
+    fn unchanged_roster_has_no_changes() {
+        let roster = [row("Peter", "peter@uci.edu", &["board"], Some(1))];
+        assert!(diff_rosters(&roster, &roster).is_empty());
+    }
+
+    #[test]
+    fn join_and_leave() {
+        let old = [row("Peter", "peter@uci.edu", &["board"], None)];
+        let new = [row("Anteater", "anteater@uci.edu", &["projects"], None)];
+        assert_eq!(
+            diff_rosters(&old, &new),
+            [
+                "Joined: **Anteater** (`anteater@uci.edu`) `projects`",
+                "Left: **Peter** (`peter@uci.edu`)",
+            ]
+        );
+    }
+
+    #[test]
+    fn committee_change() {
+        let old = [row("Peter", "peter@uci.edu", &["board", "events"], None)];
+        let new = [row("Peter", "Peter@UCI.edu", &["board", "projects"], None)];
+        assert_eq!(
+            diff_rosters(&old, &new),
+            ["**Peter** joined `projects`; left `events`"]
+        );
+    }
+
+    #[test]
+    fn email_change_matched_by_name() {
+        let old = [row("Peter", "peter@uci.edu", &["board"], None)];
+        let new = [row("Peter", "peter@icssc.club", &["board"], None)];
+        assert_eq!(
+            diff_rosters(&old, &new),
+            ["**Peter** changed email: `peter@uci.edu` \u{2192} `peter@icssc.club`"]
+        );
+    }
+
+    #[test]
+    fn email_change_and_rename_matched_by_discord_id() {
+        let old = [row("Peter", "peter@uci.edu", &["board"], Some(1))];
+        let mut renamed = row("Pete", "pete@uci.edu", &["board"], Some(1));
+        renamed.discord = "peter".to_owned();
+        assert_eq!(
+            diff_rosters(&old, &[renamed]),
+            [
+                "**Peter** is now **Pete**",
+                "**Pete** changed email: `peter@uci.edu` \u{2192} `pete@uci.edu`",
+            ]
+        );
+    }
+
+    #[test]
+    fn rename_keeping_email() {
+        let old = [row("Peter", "peter@uci.edu", &["board"], None)];
+        let mut renamed = row("Pete", "peter@uci.edu", &["board"], None);
+        renamed.discord = "peter".to_owned();
+        assert_eq!(
+            diff_rosters(&old, &[renamed]),
+            ["**Peter** is now **Pete**"]
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use anyhow::{Context as _, anyhow, bail};
+use chrono::{TimeDelta, Utc};
+use entity::{roster_link, roster_link_request};
+use poise::ChoiceParameter;
+use sea_orm::{
+    ActiveValue, ColumnTrait as _, DbErr, EntityTrait as _, QueryFilter as _,
+    TransactionTrait as _, sea_query::OnConflict,
+};
+use serenity::all::{
+    ButtonStyle, ChannelId, ComponentInteraction, CreateActionRow, CreateButton,
+    CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, Mentionable as _,
+    UserId,
+};
+
+use crate::{
+    AppContext, AppError, AppVars,
+    attendance::window::random_code,
+    util::{ContextExtras as _, gforms::submit_google_form},
+};
+
+/// How long an emailed verification code stays valid
+const CODE_TTL: TimeDelta = TimeDelta::minutes(30);
+
+#[derive(ChoiceParameter, PartialEq, Eq, Copy, Clone, Debug)]
+pub(crate) enum LinkMethod {
+    #[name = "Email me a code"]
+    EmailCode,
+    #[name = "Ask the board to approve"]
+    BoardApproval,
+}
+
+async fn upsert_request(
+    data: &AppVars,
+    user_id: UserId,
+    email: &str,
+    code: Option<String>,
+    approval_message_id: Option<i64>,
+) -> anyhow::Result<()> {
+    let request = roster_link_request::ActiveModel {
+        discord_id: ActiveValue::Set(user_id.get() as i64),
+        email: ActiveValue::Set(email.to_owned()),
+        code: ActiveValue::Set(code),
+        approval_message_id: ActiveValue::Set(approval_message_id),
+        requested_at: ActiveValue::Set(Utc::now().naive_utc()),
+    };
+
+    roster_link_request::Entity::insert(request)
+        .on_conflict(
+            OnConflict::column(roster_link_request::Column::DiscordId)
+                .update_columns([
+                    roster_link_request::Column::Email,
+                    roster_link_request::Column::Code,
+                    roster_link_request::Column::ApprovalMessageId,
+                    roster_link_request::Column::RequestedAt,
+                ])
+                .to_owned(),
+        )
+        .exec(&data.db)
+        .await
+        .context("save roster link request")?;
+
+    Ok(())
+}
+
+/// Links `user_id` to a roster email, replacing any account previously linked to it, and clears
+/// their pending request
+async fn save_link(
+    data: &AppVars,
+    user_id: UserId,
+    email: String,
+    approved_by: Option<UserId>,
+) -> anyhow::Result<()> {
+    let discord_id = user_id.get() as i64;
+    data.db
+        .transaction::<_, (), DbErr>(|txn| {
+            Box::pin(async move {
+                roster_link::Entity::delete_many()
+                    .filter(roster_link::Column::Email.eq(&email))
+                    .filter(roster_link::Column::DiscordId.ne(discord_id))
+                    .exec(txn)
+                    .await?;
+
+                let link = roster_link::ActiveModel {
+                    discord_id: ActiveValue::Set(discord_id),
+                    email: ActiveValue::Set(email),
+                    linked_at: ActiveValue::Set(Utc::now().naive_utc()),
+                    approved_by: ActiveValue::Set(approved_by.map(|id| id.get() as i64)),
+                };
+                roster_link::Entity::insert(link)
+                    .on_conflict(
+                        OnConflict::column(roster_link::Column::DiscordId)
+                            .update_columns([
+                                roster_link::Column::Email,
+                                roster_link::Column::LinkedAt,
+                                roster_link::Column::ApprovedBy,
+                            ])
+                            .to_owned(),
+                    )
+                    .exec(txn)
+                    .await?;
+
+                roster_link_request::Entity::delete_by_id(discord_id)
+                    .exec(txn)
+                    .await?;
+                Ok(())
+            })
+        })
+        .await
+        .context("save roster link")?;
+
+    data.roster.write().await.reload_links().await?;
+    Ok(())
+}
+
+/// Link your Discord account to your roster entry so lookups survive username changes
+#[poise::command(slash_command, hide_in_help, ephemeral)]
+pub(crate) async fn link(
+    ctx: AppContext<'_>,
+    #[description = "The UCI email you are on the roster with"] email: String,
+    #[description = "How to prove the email is yours"] method: Option<LinkMethod>,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    let data = ctx.data();
+    let email = email.trim().to_lowercase();
+
+    let roster = data.roster.write().await.snapshot(0).await?;
+    let Some(row) = roster
+        .rows
+        .iter()
+        .find(|row| row.email.eq_ignore_ascii_case(&email))
+    else {
+        bail!("`{email}` is not on the internal roster");
+    };
+    match roster.discord_id_of(row) {
+        Some(id) if id == ctx.author().id => {
+            ctx.reply_ephemeral("Your account is already linked to that roster entry")
+                .await?;
+            return Ok(());
+        }
+        _ if row.discord_id.is_some() => {
+            bail!("That roster entry lists a different Discord account; ask the board to fix it")
+        }
+        _ => {}
+    }
+
+    let form = &data.env.roster_link_form;
+    let method = method.unwrap_or(match form.id.is_empty() {
+        true => LinkMethod::BoardApproval,
+        false => LinkMethod::EmailCode,
+    });
+
+    let response = match method {
+        LinkMethod::EmailCode => {
+            if form.id.is_empty() {
+                bail!("Email codes are not set up; ask the board to approve instead");
+            }
+
+            let code = random_code();
+            let payload = [
+                ("emailAddress", email.as_str()),
+                (&form.code_input_id, &code),
+            ];
+            submit_google_form(&data.http.client, &form.id, &payload)
+                .await
+                .map_err(|err| {
+                    dbg!(err);
+                    anyhow!("Unable to send a code. Please try again later.")
+                })?;
+            upsert_request(data, ctx.author().id, &email, Some(code), None).await?;
+
+            format!(
+                "Sent a verification code to `{email}`. Run `/roster verify` with it within {} \
+                minutes.",
+                CODE_TTL.num_minutes()
+            )
+        }
+        LinkMethod::BoardApproval => {
+            let message = ChannelId::new(data.channels.bot_log_channel_id)
+                .send_message(
+                    ctx.http(),
+                    CreateMessage::new()
+                        .content(format!(
+                            "{} wants to link their Discord account to **{}** (`{email}`)",
+                            ctx.author().mention(),
+                            row.name
+                        ))
+                        .components(vec![CreateActionRow::Buttons(vec![
+                            CreateButton::new("roster_link_approve")
+                                .label("Approve")
+                                .style(ButtonStyle::Success),
+                            CreateButton::new("roster_link_deny")
+                                .label("Deny")
+                                .style(ButtonStyle::Danger),
+                        ])]),
+                )
+                .await?;
+            upsert_request(
+                data,
+                ctx.author().id,
+                &email,
+                None,
+                Some(message.id.get() as i64),
+            )
+            .await?;
+
+            String::from(
+                "Asked the board to approve linking your account. You'll get a DM once they do.",
+            )
+        }
+    };
+
+    ctx.reply_ephemeral(response).await?;
+    Ok(())
+}
+
+/// Finish linking your account with the code sent to your email
+#[poise::command(slash_command, hide_in_help, ephemeral)]
+pub(crate) async fn verify(
+    ctx: AppContext<'_>,
+    #[description = "Code from the verification email"] code: String,
+) -> Result<(), AppError> {
+    let data = ctx.data();
+    let discord_id = ctx.author().id.get() as i64;
+
+    let request = roster_link_request::Entity::find_by_id(discord_id)
+        .one(&data.db)
+        .await
+        .context("fetch roster link request")?
+        .filter(|request| request.code.is_some());
+    let Some(request) = request else {
+        bail!("You have no pending email verification; start one with `/roster link`");
+    };
+
+    let expired = request.requested_at + CODE_TTL < Utc::now().naive_utc();
+    let matches = request
+        .code
+        .as_ref()
+        .is_some_and(|expected| code.trim().eq_ignore_ascii_case(expected));
+    if expired || !matches {
+        // one guess per code, so they can't be brute forced
+        roster_link_request::Entity::delete_by_id(discord_id)
+            .exec(&data.db)
+            .await
+            .context("clear roster link request")?;
+        match expired {
+            true => bail!("That code has expired; request a new one with `/roster link`"),
+            false => bail!("That code is incorrect; request a new one with `/roster link`"),
+        }
+    }
+
+    let email = request.email;
+    save_link(data, ctx.author().id, email.clone(), None).await?;
+
+    ctx.reply_ephemeral(format!("Linked your account to `{email}`"))
+        .await?;
+    Ok(())
+}
+
+/// Unlink your Discord account from your roster entry
+#[poise::command(slash_command, hide_in_help, ephemeral)]
+pub(crate) async fn unlink(ctx: AppContext<'_>) -> Result<(), AppError> {
+    let data = ctx.data();
+    let deleted = roster_link::Entity::delete_by_id(ctx.author().id.get() as i64)
+        .exec(&data.db)
+        .await
+        .context("delete roster link")?;
+    data.roster.write().await.reload_links().await?;
+
+    ctx.reply_ephemeral(match deleted.rows_affected {
+        0 => "Your account was not linked",
+        _ => "Unlinked your account from the roster",
+    })
+    .await?;
+    Ok(())
+}
+
+pub(crate) struct RosterLinkApproval<'a> {
+    ctx: &'a serenity::all::Context,
+    data: &'a AppVars,
+}
+
+impl<'a> RosterLinkApproval<'a> {
+    pub(crate) fn new(ctx: &'a serenity::all::Context, data: &'a AppVars) -> Self {
+        Self { ctx, data }
+    }
+
+    /// The request behind the clicked message, as long as a board member clicked it
+    async fn pending_request(
+        &self,
+        interaction: &ComponentInteraction,
+    ) -> anyhow::Result<roster_link_request::Model> {
+        let is_board = self
+            .data
+            .roster
+            .write()
+            .await
+            .get_user_from_discord(&interaction.user, false)
+            .await?
+            .is_some_and(|row| row.is_board());
+        if !is_board {
+            bail!("Only board members can review roster links");
+        }
+
+        roster_link_request::Entity::find()
+            .filter(
+                roster_link_request::Column::ApprovalMessageId
+                    .eq(interaction.message.id.get() as i64),
+            )
+            .one(&self.data.db)
+            .await
+            .context("fetch roster link request")?
+            .context("This request was already handled or replaced by a newer one")
+    }
+
+    async fn resolve(
+        &self,
+        interaction: &ComponentInteraction,
+        outcome: &str,
+    ) -> anyhow::Result<()> {
+        interaction
+            .create_response(
+                self.ctx,
+                CreateInteractionResponse::UpdateMessage(
+                    CreateInteractionResponseMessage::new()
+                        .content(format!(
+                            "{}\n-# {outcome} by {}",
+                            interaction.message.content,
+                            interaction.user.mention()
+                        ))
+                        .components(vec![]),
+                ),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub(crate) async fn approve(&self, interaction: &ComponentInteraction) -> anyhow::Result<()> {
+        let request = self.pending_request(interaction).await?;
+        let user_id = UserId::new(request.discord_id as u64);
+        save_link(
+            self.data,
+            user_id,
+            request.email.clone(),
+            Some(interaction.user.id),
+        )
+        .await?;
+        self.resolve(interaction, "Approved").await?;
+
+        let _ = user_id
+            .direct_message(
+                self.ctx,
+                CreateMessage::new().content(format!(
+                    "Your Discord account is now linked to `{}` on the ICSSC roster",
+                    request.email
+                )),
+            )
+            .await;
+        Ok(())
+    }
+
+    pub(crate) async fn deny(&self, interaction: &ComponentInteraction) -> anyhow::Result<()> {
+        let request = self.pending_request(interaction).await?;
+        roster_link_request::Entity::delete_by_id(request.discord_id)
+            .exec(&self.data.db)
+            .await
+            .context("delete roster link request")?;
+        self.resolve(interaction, "Denied").await
+    }
+}
@@ -1,3 +1,6 @@
+pub(crate) mod boarding;
 pub(crate) mod command;
 pub(crate) mod desynced;
+pub(crate) mod feed;
+pub(crate) mod link;
 pub(crate) mod user_lookup;
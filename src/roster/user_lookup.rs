@@ -17,7 +17,7 @@ pub(crate) async fn user_lookup(ctx: AppContext<'_>, user: User) -> Result<(), A
     };
 
     let mut roster = ctx.data().roster.write().await;
-    let row = roster.get_user_from_discord(&user.name, true).await?;
+    let row = roster.get_user_from_discord(&user, true).await?;
     let Some(row) = row else {
         ctx.reply_ephemeral("User is not an internal member")
             .await?;
@@ -55,7 +55,7 @@ async fn lookup_result_embed(
     let RosterSheetRow { name, email, .. } = roster_row;
 
     let user_lines = format!("Discord: {}\nEmail: {email}", user.mention());
-    let events_lines = get_events_attended_text(data, &email).await?;
+    let events_lines = get_events_attended_text(data, Some(&email), user.id).await?;
     let events_header = match events_lines.len() {
         0 => "No events attended :(".to_owned(),
         ct => format!("## Events Attended ({ct})"),
@@ -90,7 +90,7 @@ pub(crate) async fn lookup_discord(ctx: AppContext<'_>, user: User) -> Result<()
     };
 
     let mut roster = ctx.data().roster.write().await;
-    let row = roster.get_user_from_discord(&user.name, true).await?;
+    let row = roster.get_user_from_discord(&user, true).await?;
     let Some(row) = row else {
         ctx.reply_ephemeral("User is not an internal member")
             .await?;
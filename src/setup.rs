@@ -1,5 +1,6 @@
+use crate::permissions::check::check_command_invocation;
 use crate::util::ContextExtras as _;
-use crate::{AppError, AppVars, AppVarsInner, Vars, meta, roster, shortlink};
+use crate::{AppError, AppVars, AppVarsInner, Vars, meta, permissions, roster, shortlink};
 use crate::{attendance, bitsnbytes, internal_commands, matchy, spottings};
 use clap::ArgMatches;
 use itertools::Itertools as _;
@@ -18,7 +19,17 @@ pub(crate) fn load_env(args: &ArgMatches) {
 }
 
 // Env Setup
+/// Reads an on/off env var; empty means off
+pub(crate) fn parse_flag(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "" | "0" | "false" | "no" | "off" => Some(false),
+        "1" | "true" | "yes" | "on" => Some(true),
+        _ => None,
+    }
+}
+
 pub(crate) struct ChannelVars {
+    pub(crate) bot_log_channel_id: u64,
     pub(crate) icssc_guild_id: u64,
     pub(crate) matchy_channel_id: u64,
     pub(crate) spottings_channel_id: u64,
@@ -27,6 +38,12 @@ pub(crate) struct ChannelVars {
 impl ChannelVars {
     pub(crate) fn new(env: &Vars) -> Self {
         Self {
+            bot_log_channel_id: env
+                .bot
+                .channels
+                .bot_log
+                .parse::<_>()
+                .expect("BOT__CHANNELS__BOT_LOG must be valid u64"),
             icssc_guild_id: env
                 .bot
                 .channels
@@ -145,6 +162,10 @@ fn handle_framework_error(error: FrameworkError<'_, AppVars, AppError>) -> BoxFu
                 ctx.reply_ephemeral(format!("An unexpected error occurred: {wrapped_error:?}"))
                     .await
             }
+            FrameworkError::CommandCheckFailed {
+                error: Some(reason),
+                ..
+            } => ctx.reply_ephemeral(reason.to_string()).await,
             _ => ctx.reply_ephemeral("An unknown error occurred").await,
         };
         if let Err(e) = error_res {
@@ -154,27 +175,17 @@ fn handle_framework_error(error: FrameworkError<'_, AppVars, AppError>) -> BoxFu
     .boxed()
 }
 
-// fn check_command_invocation(
-//     ctx: poise::Context<AppVars, AppError>,
-// ) -> BoxFuture<Result<bool, AppError>> {
-//     const ICSSC_SERVER: u64 = 760915616793755669;
-//     const ALLOWED_CHANNELS: &[u64] = &[1328907402321592391, 1338632123929591970];
-//
-//     async move {
-//         Ok(ctx.guild_id() != Some(GuildId::from(ICSSC_SERVER))
-//             || ALLOWED_CHANNELS.contains(&ctx.channel_id().into()))
-//     }
-//     .boxed()
-// }
-
 fn get_bot_commands() -> Vec<Command<AppVars, AppError>> {
     vec![
         attendance::attended::attended(),
         attendance::checkin::checkin(),
         attendance::checkin::log_attendance(),
+        attendance::command::attendance(),
+        bitsnbytes::command::bnb(),
         bitsnbytes::meetup::log_bnb_meetup_message(),
         matchy::command::matchy(),
         meta::ping::ping(),
+        permissions::command::permissions(),
         roster::user_lookup::user_lookup(),
         roster::command::roster(),
         spottings::command::spottings(),
@@ -188,7 +199,7 @@ pub(crate) fn create_bot_framework_options() -> FrameworkOptions<AppVars, AppErr
     FrameworkOptions {
         on_error: handle_framework_error,
         commands: get_bot_commands(),
-        // command_check: Some(check_command_invocation),
+        command_check: Some(check_command_invocation),
         ..Default::default()
     }
 }
@@ -1,7 +1,9 @@
 use crate::spottings::{
+    export::export,
     leaderboard::leaderboard,
     log::history,
-    privacy::{check_snipes_participation, set_snipes_participation},
+    privacy::{check_snipes_participation, delete_my_data, set_snipes_participation},
+    stats::check_stats,
 };
 use crate::{AppContext, AppError};
 
@@ -13,6 +15,9 @@ use crate::{AppContext, AppError};
         "history",
         "check_snipes_participation",
         "set_snipes_participation",
+        "delete_my_data",
+        "check_stats",
+        "export",
     ),
     guild_only
 )]
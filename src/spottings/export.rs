@@ -0,0 +1,188 @@
+use crate::spottings::util::{
+    history_hidden_user_ids, opted_out_among, parse_date_bound, remove_hidden_users,
+};
+use crate::util::time::from_utc;
+use crate::{AppContext, AppError};
+use anyhow::Context as _;
+use chrono_tz::Tz;
+use entity::{spotting_message, spotting_victim};
+use itertools::Itertools as _;
+use poise::{ChoiceParameter, CreateReply};
+use sea_orm::{ColumnTrait as _, EntityTrait as _, QueryFilter as _, QueryOrder as _};
+use serde_json::json;
+use serenity::all::{CreateAttachment, UserId};
+
+#[derive(ChoiceParameter, PartialEq, Eq, Copy, Clone, Debug)]
+enum ExportFormat {
+    #[name = "CSV (one row per victim)"]
+    Csv,
+    #[name = "JSON"]
+    Json,
+}
+
+const CSV_HEADER: [&str; 9] = [
+    "message_id",
+    "time_posted",
+    "type",
+    "author_id",
+    "victim_id",
+    "latitude",
+    "longitude",
+    "notes",
+    "link",
+];
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn message_link(msg: &spotting_message::Model) -> String {
+    format!(
+        "https://discord.com/channels/{}/{}/{}",
+        msg.guild_id, msg.channel_id, msg.message_id
+    )
+}
+
+fn spotting_type(msg: &spotting_message::Model) -> &'static str {
+    if msg.is_social { "social" } else { "snipe" }
+}
+
+fn to_csv(tz: Tz, spottings: &[(spotting_message::Model, Vec<spotting_victim::Model>)]) -> String {
+    let rows = spottings.iter().flat_map(|(msg, victims)| {
+        victims.iter().map(move |victim| {
+            [
+                msg.message_id.to_string(),
+                from_utc(tz, msg.time_posted).to_rfc3339(),
+                spotting_type(msg).to_owned(),
+                msg.author_id.to_string(),
+                victim.victim_id.to_string(),
+                victim.latitude.map(|it| it.to_string()).unwrap_or_default(),
+                victim
+                    .longitude
+                    .map(|it| it.to_string())
+                    .unwrap_or_default(),
+                victim.notes.clone().unwrap_or_default(),
+                message_link(msg),
+            ]
+            .iter()
+            .map(|field| csv_field(field))
+            .join(",")
+        })
+    });
+
+    std::iter::once(CSV_HEADER.join(","))
+        .chain(rows)
+        .map(|line| line + "\r\n")
+        .collect()
+}
+
+fn to_json(
+    tz: Tz,
+    spottings: &[(spotting_message::Model, Vec<spotting_victim::Model>)],
+) -> serde_json::Result<String> {
+    let entries = spottings
+        .iter()
+        .map(|(msg, victims)| {
+            json!({
+                // ids are strings since they don't fit in a JS number
+                "message_id": msg.message_id.to_string(),
+                "time_posted": from_utc(tz, msg.time_posted).to_rfc3339(),
+                "type": spotting_type(msg),
+                "author_id": msg.author_id.to_string(),
+                "link": message_link(msg),
+                "victims": victims
+                    .iter()
+                    .map(|victim| json!({
+                        "victim_id": victim.victim_id.to_string(),
+                        "latitude": victim.latitude,
+                        "longitude": victim.longitude,
+                        "notes": victim.notes,
+                    }))
+                    .collect_vec(),
+            })
+        })
+        .collect_vec();
+
+    serde_json::to_string_pretty(&entries)
+}
+
+/// Export raw spottings data as a CSV or JSON file
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    ephemeral,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn export(
+    ctx: AppContext<'_>,
+    #[description = "File format"] format: ExportFormat,
+    #[description = "Only spottings on or after this date (YYYY-MM-DD)"] after: Option<String>,
+    #[description = "Only spottings on or before this date (YYYY-MM-DD)"] before: Option<String>,
+    #[description = "Leave out members who have opted out of spottings"] exclude_opted_out: Option<
+        bool,
+    >,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    let conn = &ctx.data().db;
+
+    let mut query = spotting_message::Entity::find();
+    if let Some(after) = after {
+        let after = parse_date_bound(ctx.data().timezone, &after, false)?;
+        query = query.filter(spotting_message::Column::TimePosted.gte(after));
+    }
+    if let Some(before) = before {
+        let before = parse_date_bound(ctx.data().timezone, &before, true)?;
+        query = query.filter(spotting_message::Column::TimePosted.lt(before));
+    }
+
+    let mut spottings = query
+        .order_by_asc(spotting_message::Column::TimePosted)
+        .find_with_related(spotting_victim::Entity)
+        .all(conn)
+        .await
+        .context("export fetch spottings")?;
+
+    let mut excluded = history_hidden_user_ids(conn).await?;
+    if exclude_opted_out.unwrap_or(false) {
+        let everyone = spottings
+            .iter()
+            .flat_map(|(msg, victims)| {
+                std::iter::once(msg.author_id)
+                    .chain(victims.iter().map(|victim| victim.victim_id))
+                    .map(|id| UserId::new(id as u64))
+            })
+            .unique()
+            .collect_vec();
+        excluded.extend(
+            opted_out_among(conn, everyone.into_iter())
+                .await?
+                .map(i64::from),
+        );
+    }
+    remove_hidden_users(&mut spottings, &excluded);
+
+    let (contents, extension) = match format {
+        ExportFormat::Csv => (to_csv(ctx.data().timezone, &spottings), "csv"),
+        ExportFormat::Json => (
+            to_json(ctx.data().timezone, &spottings).context("serialize spottings")?,
+            "json",
+        ),
+    };
+
+    ctx.send(
+        CreateReply::default()
+            .content(format!("Exported {} spottings", spottings.len()))
+            .attachment(CreateAttachment::bytes(
+                contents.into_bytes(),
+                format!("spottings.{extension}"),
+            ))
+            .ephemeral(true),
+    )
+    .await?;
+
+    Ok(())
+}
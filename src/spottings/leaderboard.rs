@@ -1,3 +1,4 @@
+use crate::spottings::util::history_hidden_users;
 use crate::util::paginate::{EmbedLinePaginator, PaginatorOptions};
 use crate::{AppContext, AppError};
 use anyhow::{Context as _, anyhow};
@@ -30,6 +31,7 @@ async fn show_summary_leaderboard(ctx: AppContext<'_>) -> anyhow::Result<()> {
     let conn = &ctx.data().db;
 
     let top5_overall = user_stat::Entity::find()
+        .filter(user_stat::Column::Id.not_in_subquery(history_hidden_users()))
         .order_by_desc(
             Expr::col(user_stat::Column::SnipesInitiated)
                 .add(Expr::col(user_stat::Column::SocialsInitiated).mul(2))
@@ -53,6 +55,7 @@ async fn show_summary_leaderboard(ctx: AppContext<'_>) -> anyhow::Result<()> {
         .join("\n");
 
     let top_sniper = user_stat::Entity::find()
+        .filter(user_stat::Column::Id.not_in_subquery(history_hidden_users()))
         .order_by_desc(user_stat::Column::SnipesInitiated)
         .limit(1)
         .one(conn)
@@ -67,6 +70,7 @@ async fn show_summary_leaderboard(ctx: AppContext<'_>) -> anyhow::Result<()> {
         .ok_or(anyhow!("missing top sniper"))?;
 
     let top_social = user_stat::Entity::find()
+        .filter(user_stat::Column::Id.not_in_subquery(history_hidden_users()))
         .order_by_desc(
             Expr::col(user_stat::Column::SocialsInitiated)
                 .add(Expr::col(user_stat::Column::SocialsVictim)),
@@ -120,6 +124,7 @@ pub(crate) async fn leaderboard(
 
     let lines = match by {
         LeaderboardBy::SocialCount => user_stat::Entity::find()
+            .filter(user_stat::Column::Id.not_in_subquery(history_hidden_users()))
             .order_by_desc(
                 Expr::col(user_stat::Column::SocialsInitiated)
                     .add(Expr::col(user_stat::Column::SocialsVictim)),
@@ -138,6 +143,7 @@ pub(crate) async fn leaderboard(
             })
             .collect_vec(),
         LeaderboardBy::SnipeCount => user_stat::Entity::find()
+            .filter(user_stat::Column::Id.not_in_subquery(history_hidden_users()))
             .order_by_desc(user_stat::Column::SnipesInitiated)
             .all(&ctx.data().db)
             .await
@@ -155,6 +161,7 @@ pub(crate) async fn leaderboard(
             })
             .collect_vec(),
         LeaderboardBy::VictimCount => user_stat::Entity::find()
+            .filter(user_stat::Column::Id.not_in_subquery(history_hidden_users()))
             .order_by_desc(user_stat::Column::SnipesVictim)
             .all(&ctx.data().db)
             .await
@@ -172,6 +179,7 @@ pub(crate) async fn leaderboard(
             })
             .collect_vec(),
         LeaderboardBy::SnipeRate => user_stat::Entity::find()
+            .filter(user_stat::Column::Id.not_in_subquery(history_hidden_users()))
             .select_only()
             .column(user_stat::Column::Id)
             .column_as(
@@ -1,25 +1,28 @@
-use crate::spottings::util::opted_out_among;
+use crate::spottings::util::{
+    history_hidden_user_ids, opted_out_among, parse_date_bound, remove_hidden_users,
+};
+use crate::util::ContextExtras as _;
+use crate::util::components::{
+    FLOW_TIMEOUT, choice_select_row, confirm_buttons_row, fit_select_users, resolve_guild_members,
+    user_select_row,
+};
 use crate::util::message::get_members;
-use crate::util::modal::ModalInputTexts;
 use crate::util::paginate::{EmbedLinePaginator, PaginatorOptions};
 use crate::util::text::comma_join;
 use crate::{AppContext, AppError, AppVars};
-use anyhow::{Context as _, bail};
+use anyhow::{Context as _, anyhow, bail};
 use entity::{spotting_message, spotting_victim};
 use itertools::Itertools as _;
-use poise::ChoiceParameter;
-use sea_orm::{
-    ActiveValue, ConnectionTrait as _, DbErr, EntityTrait as _, QueryOrder as _,
-    TransactionTrait as _,
-};
+use poise::{ChoiceParameter, CreateReply};
+use sea_orm::sea_query::Query;
+use sea_orm::{ActiveValue, DbErr, EntityTrait as _, QueryOrder as _, TransactionTrait as _};
+use sea_orm::{ColumnTrait as _, QueryFilter as _};
 use sea_orm::{DatabaseConnection, TransactionError};
 use serenity::all::{
-    CacheHttp as _, CreateActionRow, CreateInputText, CreateInteractionResponse,
-    CreateInteractionResponseMessage, CreateModal, GuildId, InputTextStyle, Mentionable as _,
-    ModalInteraction, ReactionType, UserId,
+    CacheHttp as _, ComponentInteractionDataKind, CreateActionRow, CreateInteractionResponse,
+    CreateInteractionResponseMessage, GuildId, Mentionable, ReactionType, UserId,
 };
 use std::num::NonZeroUsize;
-use std::str::FromStr as _;
 
 #[derive(PartialEq, Eq, ChoiceParameter)]
 enum SpottingType {
@@ -29,7 +32,7 @@ enum SpottingType {
 
 async fn add_spottings_to_db(
     conn: &DatabaseConnection,
-    r#type: SpottingType,
+    r#type: &SpottingType,
     guild_id: GuildId,
     message: &serenity::all::Message,
     victims: impl IntoIterator<Item = UserId>,
@@ -69,9 +72,6 @@ async fn add_spottings_to_db(
                 .exec(txn)
                 .await?;
 
-            txn.execute_unprepared("REFRESH MATERIALIZED VIEW user_stat")
-                .await?;
-
             Ok(())
         })
     })
@@ -80,108 +80,149 @@ async fn add_spottings_to_db(
     Ok(())
 }
 
+fn spotting_components(spotted: &[UserId], spotting_type: &SpottingType) -> Vec<CreateActionRow> {
+    vec![
+        user_select_row("spotting_log_spotted", "Who was spotted?", spotted),
+        choice_select_row(
+            "spotting_log_type",
+            &[SpottingType::Snipe.name(), SpottingType::Social.name()],
+            spotting_type.name(),
+        ),
+        confirm_buttons_row("spotting_log"),
+    ]
+}
+
 #[poise::command(context_menu_command = "Log Spotting", guild_only)]
 pub(crate) async fn log_message_spotting(
     ctx: AppContext<'_>,
     message: serenity::all::Message,
 ) -> Result<(), AppError> {
-    let spotted = get_members(&message, false);
+    let guild_id = ctx.guild_id().context("command is guild_only")?;
 
-    let AppContext::Application(ctx) = ctx else {
-        bail!("unexpected context type");
-    };
+    if message
+        .attachments
+        .iter()
+        .all(|attachment| attachment.height.is_none())
+    {
+        ctx.reply_ephemeral("No images in this message!").await?;
+        return Ok(());
+    }
 
-    // TODO update when labels are supported
-    // let spotter_input = CreateActionRow::InputText(
-    //     CreateSelectMenu::new("spotting_modal_spotter", CreateSelectMenuKind::User {
-    //         default_users: Some(vec![message.author.id])
-    //     })
-    // );
-    // let spotted_input = CreateActionRow::SelectMenu(
-    //     CreateSelectMenu::new("spotting_modal_spotted", CreateSelectMenuKind::User {
-    //         default_users: Some(spotted)
-    //     })
-    // );
-    let msg_input = CreateActionRow::InputText(
-        CreateInputText::new(InputTextStyle::Short, "Message ID", "spotting_modal_msg")
-            .value(message.id.to_string())
-            .required(true),
-    );
+    let mut spotted = get_members(&message, false);
+    let mut spotting_type = SpottingType::Snipe;
+    let content = match fit_select_users(&mut spotted) {
+        Some(note) => format!("Logging spotting for {}\n{note}", message.link()),
+        None => format!("Logging spotting for {}", message.link()),
+    };
 
-    let spotted_input = CreateActionRow::InputText(
-        CreateInputText::new(
-            InputTextStyle::Paragraph,
-            "Who was spotted?",
-            "spotting_modal_spotted",
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(content)
+                .components(spotting_components(&spotted, &spotting_type))
+                .ephemeral(true),
         )
-        .value(spotted.iter().join("\n"))
-        .required(true),
-    );
+        .await?;
+    let sent = handle.message().await?;
 
-    let spotting_type_input = CreateActionRow::InputText(
-        CreateInputText::new(
-            InputTextStyle::Short,
-            "Type of Spotting (snipe | social)",
-            "spotting_type",
-        )
-        .value("snipe")
-        .required(true),
-    );
+    while let Some(ixn) = sent
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(FLOW_TIMEOUT)
+        .await
+    {
+        match (ixn.data.custom_id.as_str(), &ixn.data.kind) {
+            ("spotting_log_spotted", ComponentInteractionDataKind::UserSelect { values }) => {
+                spotted.clone_from(values);
+            }
+            ("spotting_log_type", ComponentInteractionDataKind::StringSelect { values }) => {
+                spotting_type = match values.first().map(String::as_str) {
+                    Some("Social") => SpottingType::Social,
+                    _ => SpottingType::Snipe,
+                };
+            }
+            ("spotting_log_confirm", _) => {
+                let logged = confirm_message_spotting(
+                    ctx.serenity_context(),
+                    ctx.data(),
+                    guild_id,
+                    &message,
+                    &spotting_type,
+                    &spotted,
+                )
+                .await;
 
-    let modal = CreateModal::new("spotting_modal_confirm", "Confirm Spotting").components(vec![
-        msg_input,
-        spotted_input,
-        spotting_type_input,
-    ]);
+                let (content, components, done) = match logged {
+                    Ok(content) => (content, vec![], true),
+                    Err(why) => (
+                        why.to_string(),
+                        spotting_components(&spotted, &spotting_type),
+                        false,
+                    ),
+                };
+                ixn.create_response(
+                    ctx.http(),
+                    CreateInteractionResponse::UpdateMessage(
+                        CreateInteractionResponseMessage::new()
+                            .content(content)
+                            .components(components),
+                    ),
+                )
+                .await?;
+                if done {
+                    return Ok(());
+                }
+                continue;
+            }
+            ("spotting_log_cancel", _) => {
+                ixn.create_response(
+                    ctx.http(),
+                    CreateInteractionResponse::UpdateMessage(
+                        CreateInteractionResponseMessage::new()
+                            .content("Cancelled, nothing was logged")
+                            .components(vec![]),
+                    ),
+                )
+                .await?;
+                return Ok(());
+            }
+            _ => {}
+        }
 
-    let reply = CreateInteractionResponse::Modal(modal);
+        ixn.create_response(ctx.http(), CreateInteractionResponse::Acknowledge)
+            .await?;
+    }
 
-    ctx.interaction.create_response(ctx.http(), reply).await?;
+    handle
+        .edit(
+            ctx,
+            CreateReply::default()
+                .content("Timed out, nothing was logged")
+                .components(vec![]),
+        )
+        .await?;
 
     Ok(())
 }
 
-pub(crate) async fn confirm_message_spotting_modal(
+async fn confirm_message_spotting(
     ctx: &serenity::all::Context,
-    data: &'_ AppVars,
-    ixn: &ModalInteraction,
-) -> Result<(), AppError> {
-    let inputs = ModalInputTexts::new(ixn);
-    let message = inputs
-        .get_required_value("spotting_modal_msg")?
-        .parse::<u64>()
-        .context("unexpected non-numerical message ID")
-        .map(|id| ixn.channel_id.message(ctx.http(), id))?
-        .await?;
-
-    if message
-        .attachments
-        .iter()
-        .all(|attachment| attachment.height.is_none())
-    {
-        bail!("No images in your linked message!");
-    }
-
-    let spotted_uids = inputs
-        .get_required_value("spotting_modal_spotted")?
-        .split('\n')
-        .filter_map(|s| {
-            // TODO validate that user ids are actually in the server
-            UserId::from_str(s.trim()).ok()
-        })
+    data: &AppVars,
+    guild_id: GuildId,
+    message: &serenity::all::Message,
+    spotting_type: &SpottingType,
+    spotted: &[UserId],
+) -> anyhow::Result<String> {
+    let spotted = resolve_guild_members(ctx, guild_id, spotted)
+        .await?
+        .into_iter()
+        .map(|member| member.user.id)
         .collect_vec();
 
-    // TODO components v2 dropdown
-    let spotting_type = match inputs.get_required_value("spotting_type")?.as_str() {
-        "snipe" => SpottingType::Snipe,
-        "social" => SpottingType::Social,
-        _ => bail!("unexpected spotting type"),
-    };
-
-    if spotting_type == SpottingType::Snipe
+    if *spotting_type == SpottingType::Snipe
         && let opted_out = opted_out_among(
             &data.db,
-            std::iter::once(message.author.id).chain(spotted_uids.iter().copied()),
+            std::iter::once(message.author.id).chain(spotted.iter().copied()),
         )
         .await?
         .collect_vec()
@@ -202,45 +243,77 @@ pub(crate) async fn confirm_message_spotting_modal(
     );
 
     // write snipe to db
-    let response = match add_spottings_to_db(
+    add_spottings_to_db(
         &data.db,
         spotting_type,
-        ixn.guild_id.unwrap(),
-        &message,
-        spotted_uids,
+        guild_id,
+        message,
+        spotted.iter().copied(),
     )
     .await
-    {
-        Ok(_) => "ok, logged",
-        _ => "couldn't insert :(",
-    };
-
-    ixn.create_response(
-        ctx.http(),
-        CreateInteractionResponse::Message(
-            CreateInteractionResponseMessage::new()
-                .content(response)
-                .ephemeral(true),
-        ),
-    )
-    .await?;
+    .map_err(|_| anyhow!("couldn't insert :("))?;
 
     let _ = message.react(ctx.http(), reaction).await;
 
-    Ok(())
+    Ok(format!(
+        "ok, logged {} spotting of {}",
+        spotting_type.name().to_lowercase(),
+        comma_join(spotted.iter().map(Mentionable::mention))
+    ))
 }
 
 /// View the history of past snipes
 #[poise::command(prefix_command, slash_command, guild_only)]
-pub(crate) async fn history(ctx: AppContext<'_>) -> Result<(), AppError> {
+pub(crate) async fn history(
+    ctx: AppContext<'_>,
+    #[description = "Only spottings posted by this member"] author: Option<serenity::all::User>,
+    #[description = "Only spottings of this member"] victim: Option<serenity::all::User>,
+    #[description = "Only snipes or only socials"] r#type: Option<SpottingType>,
+    #[description = "Only spottings on or after this date (YYYY-MM-DD)"] after: Option<String>,
+    #[description = "Only spottings on or before this date (YYYY-MM-DD)"] before: Option<String>,
+) -> Result<(), AppError> {
     let conn = &ctx.data().db;
 
-    let got = spotting_message::Entity::find()
+    let mut query = spotting_message::Entity::find();
+    if let Some(author) = author {
+        query = query.filter(spotting_message::Column::AuthorId.eq(author.id.get() as i64));
+    }
+    if let Some(victim) = victim {
+        query = query.filter(
+            spotting_message::Column::MessageId.in_subquery(
+                Query::select()
+                    .column(spotting_victim::Column::MessageId)
+                    .from(spotting_victim::Entity)
+                    .and_where(spotting_victim::Column::VictimId.eq(victim.id.get() as i64))
+                    .to_owned(),
+            ),
+        );
+    }
+    if let Some(r#type) = r#type {
+        query = query.filter(spotting_message::Column::IsSocial.eq(r#type == SpottingType::Social));
+    }
+    if let Some(after) = after {
+        let after = parse_date_bound(ctx.data().timezone, &after, false)?;
+        query = query.filter(spotting_message::Column::TimePosted.gte(after));
+    }
+    if let Some(before) = before {
+        let before = parse_date_bound(ctx.data().timezone, &before, true)?;
+        query = query.filter(spotting_message::Column::TimePosted.lt(before));
+    }
+
+    let mut got = query
         .order_by_desc(spotting_message::Column::MessageId)
         .find_with_related(spotting_victim::Entity)
         .all(conn)
         .await
         .context("log get recent snipes")?;
+    remove_hidden_users(&mut got, &history_hidden_user_ids(conn).await?);
+
+    if got.is_empty() {
+        ctx.reply_ephemeral("No spottings match those filters")
+            .await?;
+        return Ok(());
+    }
 
     let paginator = EmbedLinePaginator::new(
         got.iter()
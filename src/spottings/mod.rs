@@ -1,7 +1,9 @@
 pub(crate) mod check_victim;
 pub(crate) mod command;
+pub(crate) mod export;
 pub(crate) mod leaderboard;
 pub(crate) mod log;
 pub(crate) mod privacy;
 pub(crate) mod socials_role;
+pub(crate) mod stats;
 mod util;
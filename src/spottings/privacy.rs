@@ -1,10 +1,12 @@
 use crate::util::ContextExtras as _;
+use crate::util::components::{FLOW_TIMEOUT, confirm_buttons_row};
 use crate::{AppContext, AppError, AppVars};
 use anyhow::{Context as _, bail, ensure};
-use entity::snipe_opt_out;
-use poise::ChoiceParameter;
-use sea_orm::EntityTrait as _;
-use sea_orm::{ActiveValue, DbErr};
+use entity::{snipe_opt_out, spotting_message, spotting_victim};
+use poise::{ChoiceParameter, CreateReply};
+use sea_orm::sea_query::{OnConflict, Query};
+use sea_orm::{ActiveValue, ColumnTrait as _, DbErr, QueryFilter as _, TransactionTrait as _};
+use sea_orm::{EntityTrait as _, QuerySelect as _};
 use serenity::all::{
     CacheHttp as _, Channel, ChannelId, ChannelType, ComponentInteraction,
     CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, GuildChannel,
@@ -42,6 +44,7 @@ impl<'a> SnipesOptOut<'a> {
     pub(crate) async fn opt_out(&self, interaction: &ComponentInteraction) -> anyhow::Result<()> {
         let opt_out_user = snipe_opt_out::ActiveModel {
             id: ActiveValue::Set(interaction.user.id.into()),
+            hide_history: ActiveValue::Set(false),
         };
 
         let response = match self.contains_user(interaction.user.id).await {
@@ -142,13 +145,16 @@ pub(crate) async fn check_snipes_participation(ctx: AppContext<'_>) -> Result<()
         .await
         .context("get opt out user id")?;
 
-    ctx.reply_ephemeral(format!(
-        "you are opted **{}** snipes",
-        match got {
-            None => "in to",
-            Some(..) => "out of",
-        }
-    ))
+    ctx.reply_ephemeral(match got {
+        None => "you are opted **in to** snipes",
+        Some(snipe_opt_out::Model {
+            hide_history: false,
+            ..
+        }) => "you are opted **out of** snipes; your past spottings are still shown",
+        Some(snipe_opt_out::Model {
+            hide_history: true, ..
+        }) => "you are opted **out of** snipes and your past spottings are hidden",
+    })
     .await?;
     Ok(())
 }
@@ -166,6 +172,8 @@ enum OptInStatus {
 pub(crate) async fn set_snipes_participation(
     ctx: AppContext<'_>,
     #[description = "New value you want to set"] target: OptInStatus,
+    #[description = "When opting out, also hide your past spottings from history, leaderboards and exports"]
+    hide_history: Option<bool>,
 ) -> Result<(), AppError> {
     let conn = &ctx.data().db;
 
@@ -176,23 +184,146 @@ pub(crate) async fn set_snipes_participation(
                 .await
                 .context("Opt out delete user id")?;
 
-            ctx.reply_ephemeral("ok, you are now opted in; snipes including you can be logged!")
-                .await?;
+            ctx.reply_ephemeral(
+                "ok, you are now opted in; snipes including you can be logged, \
+                and any past spottings you hid are visible again!",
+            )
+            .await?;
         }
         OptInStatus::OptOut => {
+            // leave an existing choice about hiding history alone unless it was passed again
             let mdl = snipe_opt_out::ActiveModel {
                 id: ActiveValue::Set(ctx.author().id.get() as i64),
+                hide_history: hide_history.map_or(ActiveValue::NotSet, ActiveValue::Set),
             };
+            let insert = snipe_opt_out::Entity::insert(mdl);
 
-            snipe_opt_out::Entity::insert(mdl)
-                .on_conflict_do_nothing()
-                .exec(conn)
-                .await?;
-            ctx.reply_ephemeral("ok, you are now opted out; nobody can log a snipe including you")
-                .await
-                .context("opt out insert user id")?;
+            match hide_history {
+                Some(_) => {
+                    insert
+                        .on_conflict(
+                            OnConflict::column(snipe_opt_out::Column::Id)
+                                .update_column(snipe_opt_out::Column::HideHistory)
+                                .to_owned(),
+                        )
+                        .exec(conn)
+                        .await
+                        .context("opt out insert user id")?;
+                }
+                None => {
+                    insert
+                        .on_conflict_do_nothing()
+                        .exec(conn)
+                        .await
+                        .context("opt out insert user id")?;
+                }
+            }
+            ctx.reply_ephemeral(if hide_history == Some(true) {
+                "ok, you are now opted out; nobody can log a snipe including you, \
+                and your past spottings are hidden until you opt back in"
+            } else {
+                "ok, you are now opted out; nobody can log a snipe including you"
+            })
+            .await?;
         }
     }
 
     Ok(())
 }
+
+/// Permanently delete every spotting you posted or appear in
+#[poise::command(slash_command, ephemeral)]
+pub(crate) async fn delete_my_data(ctx: AppContext<'_>) -> Result<(), AppError> {
+    let user_id = ctx.author().id.get() as i64;
+    let conn = &ctx.data().db;
+
+    let handle = ctx
+        .send(
+            CreateReply::default()
+                .content(
+                    "This will permanently delete every spotting you posted or appear in, \
+                    including the ones you posted of other members. This cannot be undone. \
+                    To only hide your spottings, use `/spottings set_snipes_participation` instead.",
+                )
+                .components(vec![confirm_buttons_row("spotting_delete_data")])
+                .ephemeral(true),
+        )
+        .await?;
+    let sent = handle.message().await?;
+
+    let Some(ixn) = sent
+        .await_component_interaction(&ctx.serenity_context().shard)
+        .author_id(ctx.author().id)
+        .timeout(FLOW_TIMEOUT)
+        .await
+    else {
+        handle
+            .edit(
+                ctx,
+                CreateReply::default()
+                    .content("Timed out, nothing was deleted")
+                    .components(vec![]),
+            )
+            .await?;
+        return Ok(());
+    };
+
+    let content = if ixn.data.custom_id == "spotting_delete_data_confirm" {
+        let (messages, victims) = conn
+            .transaction::<_, (u64, u64), DbErr>(|txn| {
+                Box::pin(async move {
+                    let messages = spotting_message::Entity::delete_many()
+                        .filter(spotting_message::Column::AuthorId.eq(user_id))
+                        .exec(txn)
+                        .await?;
+                    let appeared_in = spotting_victim::Entity::find()
+                        .select_only()
+                        .column(spotting_victim::Column::MessageId)
+                        .filter(spotting_victim::Column::VictimId.eq(user_id))
+                        .into_tuple::<i64>()
+                        .all(txn)
+                        .await?;
+                    let victims = spotting_victim::Entity::delete_many()
+                        .filter(spotting_victim::Column::VictimId.eq(user_id))
+                        .exec(txn)
+                        .await?;
+                    // spottings of nobody but this user are left without victims
+                    spotting_message::Entity::delete_many()
+                        .filter(spotting_message::Column::MessageId.is_in(appeared_in))
+                        .filter(
+                            spotting_message::Column::MessageId.not_in_subquery(
+                                Query::select()
+                                    .column(spotting_victim::Column::MessageId)
+                                    .from(spotting_victim::Entity)
+                                    .to_owned(),
+                            ),
+                        )
+                        .exec(txn)
+                        .await?;
+
+                    Ok((messages.rows_affected, victims.rows_affected))
+                })
+            })
+            .await
+            .context("delete spotting data")?;
+
+        format!(
+            "Deleted {messages} spottings you posted and {victims} spottings you appeared in. \
+            Your participation setting was left unchanged."
+        )
+    } else {
+        String::from("Cancelled, nothing was deleted")
+    };
+
+    ixn.create_response(
+        ctx.http(),
+        CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .content(content)
+                .components(vec![]),
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use crate::util::ContextExtras as _;
+use crate::{AppContext, AppError};
+use anyhow::Context as _;
+use entity::user_stat;
+use itertools::Itertools as _;
+use sea_orm::{
+    ConnectionTrait as _, DbErr, EntityTrait as _, FromQueryResult as _, Statement,
+    TransactionTrait as _,
+};
+use serenity::all::{Mentionable as _, UserId};
+use std::collections::HashMap;
+
+fn describe_stat(stat: Option<&user_stat::Model>) -> String {
+    match stat {
+        None => String::from("missing"),
+        Some(stat) => format!(
+            "{} snipes, {} socials, sniped {}, socialed {}",
+            stat.snipes_initiated, stat.socials_initiated, stat.snipes_victim, stat.socials_victim
+        ),
+    }
+}
+
+/// Compare the spottings stats table against a full recomputation
+#[poise::command(
+    slash_command,
+    hide_in_help,
+    ephemeral,
+    required_permissions = "ADMINISTRATOR"
+)]
+pub(crate) async fn check_stats(
+    ctx: AppContext<'_>,
+    #[description = "Rebuild the stats table from scratch if it is inconsistent"] fix: Option<bool>,
+) -> Result<(), AppError> {
+    ctx.defer_ephemeral().await?;
+    let conn = &ctx.data().db;
+
+    let actual = user_stat::Entity::find()
+        .all(conn)
+        .await
+        .context("fetch user stats")?
+        .into_iter()
+        .map(|stat| (stat.id, stat))
+        .collect::<HashMap<_, _>>();
+
+    let expected = user_stat::Model::find_by_statement(Statement::from_string(
+        conn.get_database_backend(),
+        "SELECT * FROM user_stat_expected",
+    ))
+    .all(conn)
+    .await
+    .context("recompute user stats")?
+    .into_iter()
+    .map(|stat| (stat.id, stat))
+    .collect::<HashMap<_, _>>();
+
+    let mismatched = actual
+        .keys()
+        .chain(expected.keys())
+        .unique()
+        .filter(|id| actual.get(id) != expected.get(id))
+        .sorted()
+        .collect_vec();
+
+    if mismatched.is_empty() {
+        ctx.reply_ephemeral(format!(
+            "Spottings stats are consistent ({} users checked)",
+            expected.len()
+        ))
+        .await?;
+        return Ok(());
+    }
+
+    let mut lines = mismatched
+        .iter()
+        .take(10)
+        .map(|&&id| {
+            format!(
+                "1. {}: stored {}; expected {}",
+                UserId::new(id as u64).mention(),
+                describe_stat(actual.get(&id)),
+                describe_stat(expected.get(&id)),
+            )
+        })
+        .collect_vec();
+    if mismatched.len() > lines.len() {
+        lines.push(format!("...and {} more", mismatched.len() - lines.len()));
+    }
+
+    let footer = if fix.unwrap_or(false) {
+        conn.transaction::<_, (), DbErr>(|txn| {
+            Box::pin(async move {
+                txn.execute_unprepared("LOCK TABLE user_stat IN EXCLUSIVE MODE")
+                    .await?;
+                txn.execute_unprepared("DELETE FROM user_stat").await?;
+                txn.execute_unprepared("INSERT INTO user_stat SELECT * FROM user_stat_expected")
+                    .await?;
+                Ok(())
+            })
+        })
+        .await
+        .context("rebuild user stats")?;
+        "Rebuilt the stats table from a full recomputation."
+    } else {
+        "Run again with `fix: True` to rebuild the stats table."
+    };
+
+    ctx.reply_ephemeral(format!(
+        "Found {} inconsistent users:\n{}\n\n{footer}",
+        mismatched.len(),
+        lines.join("\n")
+    ))
+    .await?;
+
+    Ok(())
+}
@@ -1,9 +1,14 @@
-use anyhow::Context as _;
-use entity::snipe_opt_out;
+use crate::util::time::start_of_day;
+use anyhow::{Context as _, anyhow};
+use chrono::{Days, NaiveDate, NaiveDateTime};
+use chrono_tz::Tz;
+use entity::{snipe_opt_out, spotting_message, spotting_victim};
 use itertools::Itertools as _;
-use sea_orm::QueryFilter as _;
+use sea_orm::sea_query::{Query, SelectStatement};
 use sea_orm::{ColumnTrait as _, DatabaseConnection, EntityTrait as _};
+use sea_orm::{QueryFilter as _, QuerySelect as _};
 use serenity::all::UserId;
+use std::collections::HashSet;
 
 pub async fn opted_out_among<Ids>(
     conn: &DatabaseConnection,
@@ -22,3 +27,48 @@ where
         .into_iter()
         .map(|opted_out| UserId::new(opted_out.id as u64)))
 }
+
+/// Parses a `YYYY-MM-DD` command option into the naive UTC time that day starts in `tz`. With
+/// `inclusive_end`, the start of the following day is returned instead.
+pub fn parse_date_bound(tz: Tz, date: &str, inclusive_end: bool) -> anyhow::Result<NaiveDateTime> {
+    let mut parsed = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| anyhow!("`{date}` is not a date in the form YYYY-MM-DD"))?;
+    if inclusive_end {
+        parsed = parsed + Days::new(1);
+    }
+    Ok(start_of_day(tz, parsed))
+}
+
+/// Subquery for the IDs of users who asked for their past spottings to be hidden
+pub fn history_hidden_users() -> SelectStatement {
+    Query::select()
+        .column(snipe_opt_out::Column::Id)
+        .from(snipe_opt_out::Entity)
+        .and_where(snipe_opt_out::Column::HideHistory.eq(true))
+        .to_owned()
+}
+
+pub async fn history_hidden_user_ids(conn: &DatabaseConnection) -> anyhow::Result<HashSet<i64>> {
+    let got = snipe_opt_out::Entity::find()
+        .select_only()
+        .column(snipe_opt_out::Column::Id)
+        .filter(snipe_opt_out::Column::HideHistory.eq(true))
+        .into_tuple::<i64>()
+        .all(conn)
+        .await
+        .context("hidden history query")?;
+
+    Ok(got.into_iter().collect())
+}
+
+/// Removes `hidden` users from spottings, dropping spottings they posted and any spotting left
+/// without victims
+pub fn remove_hidden_users(
+    spottings: &mut Vec<(spotting_message::Model, Vec<spotting_victim::Model>)>,
+    hidden: &HashSet<i64>,
+) {
+    spottings.retain_mut(|(msg, victims)| {
+        victims.retain(|victim| !hidden.contains(&victim.victim_id));
+        !hidden.contains(&msg.author_id) && !victims.is_empty()
+    });
+}
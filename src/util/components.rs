@@ -0,0 +1,112 @@
+use anyhow::bail;
+use itertools::{Either, Itertools as _};
+use serenity::all::{
+    ButtonStyle, CreateActionRow, CreateButton, CreateSelectMenu, CreateSelectMenuKind,
+    CreateSelectMenuOption, GuildId, Member, Mentionable as _, UserId,
+};
+use serenity::futures::future;
+use std::time::Duration;
+
+/// How long a component-based logging flow waits for the next interaction before giving up
+pub(crate) const FLOW_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Discord caps select menus at 25 values
+pub(crate) const MAX_SELECT_VALUES: usize = 25;
+
+/// Trims prefilled users to what a user select can hold, so a flow's state matches its picker.
+/// Returns a note about anyone left out.
+pub(crate) fn fit_select_users(users: &mut Vec<UserId>) -> Option<String> {
+    let left_out = users.len().checked_sub(MAX_SELECT_VALUES)?;
+    if left_out == 0 {
+        return None;
+    }
+
+    users.truncate(MAX_SELECT_VALUES);
+    Some(format!(
+        "Only the first {MAX_SELECT_VALUES} users fit in the picker; {left_out} more were left out"
+    ))
+}
+
+/// Expects `default_users` to have been trimmed with [`fit_select_users`]
+pub(crate) fn user_select_row(
+    custom_id: &str,
+    placeholder: &str,
+    default_users: &[UserId],
+) -> CreateActionRow {
+    let default_users = default_users.to_vec();
+
+    CreateActionRow::SelectMenu(
+        CreateSelectMenu::new(
+            custom_id,
+            CreateSelectMenuKind::User {
+                default_users: Some(default_users),
+            },
+        )
+        .placeholder(placeholder)
+        .min_values(1)
+        .max_values(MAX_SELECT_VALUES as u8),
+    )
+}
+
+/// A single-choice select menu with `selected` marked as the default option
+pub(crate) fn choice_select_row(
+    custom_id: &str,
+    choices: &[&str],
+    selected: &str,
+) -> CreateActionRow {
+    let options = choices
+        .iter()
+        .map(|&choice| {
+            CreateSelectMenuOption::new(choice, choice).default_selection(choice == selected)
+        })
+        .collect_vec();
+
+    CreateActionRow::SelectMenu(
+        CreateSelectMenu::new(custom_id, CreateSelectMenuKind::String { options })
+            .min_values(1)
+            .max_values(1),
+    )
+}
+
+/// Confirm and cancel buttons with custom IDs `{prefix}_confirm` and `{prefix}_cancel`
+pub(crate) fn confirm_buttons_row(prefix: &str) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{prefix}_confirm"))
+            .label("Confirm")
+            .style(ButtonStyle::Success),
+        CreateButton::new(format!("{prefix}_cancel"))
+            .label("Cancel")
+            .style(ButtonStyle::Secondary),
+    ])
+}
+
+/// Fetches every user as a member of the guild, failing with a list of the users that aren't
+pub(crate) async fn resolve_guild_members(
+    ctx: &serenity::all::Context,
+    guild_id: GuildId,
+    user_ids: &[UserId],
+) -> anyhow::Result<Vec<Member>> {
+    if user_ids.is_empty() {
+        bail!("Please select at least one user");
+    }
+
+    let lookups = future::join_all(user_ids.iter().map(|&uid| guild_id.member(ctx, uid))).await;
+
+    let (members, rejected): (Vec<_>, Vec<_>) =
+        lookups
+            .into_iter()
+            .zip(user_ids)
+            .partition_map(|(lookup, uid)| match lookup {
+                Ok(member) => Either::Left(member),
+                Err(_) => Either::Right(uid.mention()),
+            });
+
+    if !rejected.is_empty() {
+        bail!(
+            "The following users are not members of this server: {}",
+            rejected.iter().join(", ")
+        );
+    }
+
+    Ok(members)
+}
@@ -3,9 +3,11 @@ use std::{
     time::{SystemTime, UNIX_EPOCH},
 };
 
+use anyhow::Context as _;
 use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{EncodingKey, Header, encode};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 use crate::{AppError, AppVars, Vars, VarsServiceAccountKey};
 
@@ -95,7 +97,7 @@ impl GoogleServiceAccount {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub(crate) enum DriveFilePermissionRole {
     Owner,
@@ -106,9 +108,24 @@ pub(crate) enum DriveFilePermissionRole {
     Reader,
 }
 
+impl DriveFilePermissionRole {
+    /// The name Drive shows for this role in its sharing menu
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Self::Owner => "Owner",
+            Self::Organizer => "Manager",
+            Self::FileOrganizer => "Content Manager",
+            Self::Writer => "Editor",
+            Self::Commenter => "Commenter",
+            Self::Reader => "Viewer",
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct DriveFilePermission {
+    pub(crate) id: String,
     pub(crate) email_address: String,
     pub(crate) role: DriveFilePermissionRole,
 }
@@ -120,14 +137,49 @@ pub(crate) struct PermissionsResponse {
     next_page_token: Option<String>,
 }
 
+const DRIVE_SCOPE: &str = "https://www.googleapis.com/auth/drive";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DriveFileResponse {
+    drive_id: Option<String>,
+}
+
+/// The Shared Drive holding the roster spreadsheet. Club members are given access to the whole
+/// drive, and roles like Manager only exist on a Shared Drive, not on single files.
+async fn roster_drive_id(data: &AppVars, access_token: &str) -> Result<String, AppError> {
+    let resp = data
+        .http
+        .client
+        .get(format!(
+            "https://www.googleapis.com/drive/v3/files/{}",
+            data.env.roster_spreadsheet.id
+        ))
+        .query(&[("fields", "driveId"), ("supportsAllDrives", "true")])
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<DriveFileResponse>()
+        .await?;
+
+    resp.drive_id
+        .context("the roster spreadsheet is not in a Shared Drive")
+}
+
+fn permissions_url(drive_id: &str) -> String {
+    format!("https://www.googleapis.com/drive/v3/files/{drive_id}/permissions")
+}
+
 async fn get_permissions_page(
     data: &AppVars,
     access_token: &str,
+    drive_id: &str,
     page_token: Option<&str>,
 ) -> Result<PermissionsResponse, AppError> {
     let mut query = vec![
-        ("fields", "nextPageToken,permissions(role,emailAddress)"),
-        ("supportsTeamDrives", "true"),
+        ("fields", "nextPageToken,permissions(id,role,emailAddress)"),
+        ("supportsAllDrives", "true"),
     ];
 
     if let Some(tok) = page_token {
@@ -137,10 +189,7 @@ async fn get_permissions_page(
     let resp = data
         .http
         .client
-        .get(format!(
-            "https://www.googleapis.com/drive/v3/files/{}/permissions",
-            data.env.roster_spreadsheet.id
-        ))
+        .get(permissions_url(drive_id))
         .query(&query)
         .bearer_auth(access_token)
         .send()
@@ -151,6 +200,7 @@ async fn get_permissions_page(
     Ok(resp)
 }
 
+/// Everyone with access to the roster Shared Drive
 pub(crate) async fn get_gdrive_permissions(
     data: &AppVars,
 ) -> Result<Vec<DriveFilePermission>, AppError> {
@@ -161,11 +211,13 @@ pub(crate) async fn get_gdrive_permissions(
         .get_access_token("https://www.googleapis.com/auth/drive.metadata.readonly")
         .await?;
 
+    let drive_id = roster_drive_id(data, &access_token).await?;
     let mut next_page_token = None;
     let mut permissions = vec![];
 
     loop {
-        let resp = get_permissions_page(data, &access_token, next_page_token.as_deref()).await?;
+        let resp = get_permissions_page(data, &access_token, &drive_id, next_page_token.as_deref())
+            .await?;
 
         permissions.extend(resp.permissions);
         match resp.next_page_token {
@@ -176,3 +228,82 @@ pub(crate) async fn get_gdrive_permissions(
 
     Ok(permissions)
 }
+
+/// Adds `email` to the roster Shared Drive with `role`
+pub(crate) async fn create_gdrive_permission(
+    data: &AppVars,
+    email: &str,
+    role: &DriveFilePermissionRole,
+) -> Result<(), AppError> {
+    let access_token = data
+        .google_service_account
+        .write()
+        .await
+        .get_access_token(DRIVE_SCOPE)
+        .await?;
+    let drive_id = roster_drive_id(data, &access_token).await?;
+
+    data.http
+        .client
+        .post(permissions_url(&drive_id))
+        .query(&[("supportsAllDrives", "true")])
+        .json(&json!({ "type": "user", "role": role, "emailAddress": email }))
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Changes the role of an existing Shared Drive member
+pub(crate) async fn update_gdrive_permission(
+    data: &AppVars,
+    permission_id: &str,
+    role: &DriveFilePermissionRole,
+) -> Result<(), AppError> {
+    let access_token = data
+        .google_service_account
+        .write()
+        .await
+        .get_access_token(DRIVE_SCOPE)
+        .await?;
+    let drive_id = roster_drive_id(data, &access_token).await?;
+
+    data.http
+        .client
+        .patch(format!("{}/{permission_id}", permissions_url(&drive_id)))
+        .query(&[("supportsAllDrives", "true")])
+        .json(&json!({ "role": role }))
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+/// Removes an existing member from the roster Shared Drive
+pub(crate) async fn delete_gdrive_permission(
+    data: &AppVars,
+    permission_id: &str,
+) -> Result<(), AppError> {
+    let access_token = data
+        .google_service_account
+        .write()
+        .await
+        .get_access_token(DRIVE_SCOPE)
+        .await?;
+    let drive_id = roster_drive_id(data, &access_token).await?;
+
+    data.http
+        .client
+        .delete(format!("{}/{permission_id}", permissions_url(&drive_id)))
+        .query(&[("supportsAllDrives", "true")])
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
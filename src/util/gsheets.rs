@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use serde::Deserialize;
+use serde_json::json;
 use tokio::sync::RwLock;
 
 use crate::util::gdrive::GoogleServiceAccount;
@@ -33,3 +34,124 @@ pub(crate) async fn get_spreadsheet_range(
 
     Ok(resp)
 }
+
+/// A rectangular A1 range like `'Form Responses 1'!A2:D`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SheetRange {
+    /// Everything before the `!`, kept as written so quoted names still work
+    pub(crate) sheet: Option<String>,
+    pub(crate) start_col: u32,
+    pub(crate) start_row: u32,
+    pub(crate) end_col: u32,
+}
+
+fn column_number(letters: &str) -> Option<u32> {
+    letters.chars().try_fold(0, |acc, c| {
+        c.is_ascii_alphabetic()
+            .then(|| acc * 26 + (c.to_ascii_uppercase() as u32 - 'A' as u32 + 1))
+    })
+}
+
+fn column_letters(mut number: u32) -> String {
+    let mut letters = Vec::new();
+    while number > 0 {
+        let rem = (number - 1) % 26;
+        letters.push((b'A' + rem as u8) as char);
+        number = (number - 1) / 26;
+    }
+    letters.into_iter().rev().collect()
+}
+
+impl SheetRange {
+    pub(crate) fn parse(range: &str) -> Option<Self> {
+        let (sheet, cells) = match range.rsplit_once('!') {
+            Some((sheet, cells)) => (Some(sheet.to_owned()), cells),
+            None => (None, range),
+        };
+        let (start, end) = cells.split_once(':')?;
+
+        let split = |cell: &str| {
+            let digits_at = cell
+                .find(|c: char| c.is_ascii_digit())
+                .unwrap_or(cell.len());
+            let (letters, digits) = cell.split_at(digits_at);
+            Some((column_number(letters)?, digits.parse::<u32>().ok()))
+        };
+        let (start_col, start_row) = split(start)?;
+        let (end_col, _) = split(end)?;
+
+        Some(Self {
+            sheet,
+            start_col,
+            start_row: start_row.unwrap_or(1),
+            end_col,
+        })
+    }
+
+    /// The same range with one more column on the right
+    pub(crate) fn widened(&self) -> Self {
+        Self {
+            end_col: self.end_col + 1,
+            ..self.clone()
+        }
+    }
+
+    /// A1 name of the cell `offset` rows down in column `col`
+    pub(crate) fn cell(&self, col: u32, offset: usize) -> String {
+        let prefix = self
+            .sheet
+            .as_ref()
+            .map(|sheet| format!("{sheet}!"))
+            .unwrap_or_default();
+        format!(
+            "{prefix}{}{}",
+            column_letters(col),
+            self.start_row as usize + offset
+        )
+    }
+}
+
+impl std::fmt::Display for SheetRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(sheet) = &self.sheet {
+            write!(f, "{sheet}!")?;
+        }
+        write!(
+            f,
+            "{}{}:{}",
+            column_letters(self.start_col),
+            self.start_row,
+            column_letters(self.end_col)
+        )
+    }
+}
+
+/// Writes each `(cell, value)` pair into the spreadsheet as plain text
+pub(crate) async fn set_spreadsheet_cells(
+    service_account: Arc<RwLock<GoogleServiceAccount>>,
+    sheet_id: &str,
+    cells: &[(String, String)],
+) -> anyhow::Result<()> {
+    let access_token = service_account
+        .write()
+        .await
+        .get_access_token("https://www.googleapis.com/auth/spreadsheets")
+        .await?;
+
+    let data = cells
+        .iter()
+        .map(|(range, value)| json!({ "range": range, "values": [[value]] }))
+        .collect::<Vec<_>>();
+
+    reqwest::Client::new()
+        .post(format!(
+            "https://sheets.googleapis.com/v4/spreadsheets/{sheet_id}/values:batchUpdate"
+        ))
+        .bearer_auth(access_token)
+        .json(&json!({ "valueInputOption": "RAW", "data": data }))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
@@ -1,16 +1,12 @@
-use std::collections::HashSet;
+use itertools::Itertools as _;
+use serenity::all::{Message, UserId};
 
-use serenity::all::Message;
+pub(crate) fn get_members(message: &Message, include_author: bool) -> Vec<UserId> {
+    let author = include_author.then_some(message.author.id);
 
-pub(crate) fn get_members(message: &Message, include_author: bool) -> HashSet<String> {
-    let mut members: HashSet<String> = message
-        .mentions
-        .iter()
-        .map(|user| user.id.to_string())
-        .collect();
-
-    if include_author {
-        members.insert(message.author.id.to_string());
-    }
-    members
+    author
+        .into_iter()
+        .chain(message.mentions.iter().map(|user| user.id))
+        .unique()
+        .collect()
 }
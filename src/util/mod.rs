@@ -1,12 +1,14 @@
 pub(crate) mod calendar;
+pub(crate) mod components;
 pub(crate) mod gdrive;
 pub(crate) mod gforms;
 pub(crate) mod gsheets;
 pub(crate) mod message;
-pub(crate) mod modal;
 pub(crate) mod paginate;
 pub(crate) mod roster;
+pub(crate) mod roster_source;
 pub(crate) mod text;
+pub(crate) mod time;
 
 use crate::AppContext;
 use poise::{CreateReply, ReplyHandle};
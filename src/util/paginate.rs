@@ -2,8 +2,8 @@ use crate::util::spottings_embed;
 use crate::{AppContext, AppError};
 use poise::CreateReply;
 use serenity::all::{
-    CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
-    CreateInteractionResponseMessage, ReactionType,
+    ButtonStyle, CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter,
+    CreateInteractionResponseMessage, CreateQuickModal, ReactionType,
 };
 use serenity::builder::CreateInteractionResponse;
 use std::cmp::min;
@@ -17,6 +17,8 @@ pub(crate) struct PaginatorOptions {
     char_limit: usize,
     reply: bool,
     ephemeral: bool,
+    // each page is this embed with its description replaced
+    embed: CreateEmbed,
 }
 
 impl Default for PaginatorOptions {
@@ -27,6 +29,7 @@ impl Default for PaginatorOptions {
             char_limit: 4096,
             ephemeral: false,
             reply: true,
+            embed: spottings_embed(),
         }
     }
 }
@@ -60,12 +63,17 @@ impl PaginatorOptions {
         self.ephemeral = ephemeral;
         self
     }
+
+    pub fn embed(mut self, embed: CreateEmbed) -> Self {
+        self.embed = embed;
+        self
+    }
 }
 
 pub(crate) struct EmbedLinePaginator {
     options: PaginatorOptions,
     pages: Vec<String>,
-    current_page: u8,
+    current_page: usize,
 }
 
 impl EmbedLinePaginator {
@@ -99,9 +107,11 @@ impl EmbedLinePaginator {
         }
     }
 
-    fn embed_for(&self, _ctx: AppContext<'_>, page: u8) -> CreateEmbed {
-        spottings_embed()
-            .description(self.pages[(page - 1) as usize].clone())
+    fn embed_for(&self, _ctx: AppContext<'_>, page: usize) -> CreateEmbed {
+        self.options
+            .embed
+            .clone()
+            .description(self.pages[page - 1].clone())
             .footer(CreateEmbedFooter::new(format!(
                 "{page}/{}",
                 self.pages.len()
@@ -110,18 +120,25 @@ impl EmbedLinePaginator {
 
     pub(crate) async fn run(mut self, ctx: AppContext<'_>) -> Result<(), AppError> {
         let components = if self.pages.len() > 1 {
-            vec![CreateActionRow::Buttons(vec![
-                CreateButton::new("embedinator_start")
-                    .emoji(ReactionType::Unicode(String::from("⏮️"))),
-                CreateButton::new("embedinator_previous")
-                    .emoji(ReactionType::Unicode(String::from("◀️"))),
-                CreateButton::new("embedinator_next")
-                    .emoji(ReactionType::Unicode(String::from("▶️"))),
-                CreateButton::new("embedinator_end")
-                    .emoji(ReactionType::Unicode(String::from("⏭️"))),
-                CreateButton::new("embedinator_stop")
-                    .emoji(ReactionType::Unicode(String::from("⏹️"))),
-            ])]
+            vec![
+                CreateActionRow::Buttons(vec![
+                    CreateButton::new("embedinator_start")
+                        .emoji(ReactionType::Unicode(String::from("⏮️"))),
+                    CreateButton::new("embedinator_previous")
+                        .emoji(ReactionType::Unicode(String::from("◀️"))),
+                    CreateButton::new("embedinator_next")
+                        .emoji(ReactionType::Unicode(String::from("▶️"))),
+                    CreateButton::new("embedinator_end")
+                        .emoji(ReactionType::Unicode(String::from("⏭️"))),
+                    CreateButton::new("embedinator_stop")
+                        .emoji(ReactionType::Unicode(String::from("⏹️"))),
+                ]),
+                CreateActionRow::Buttons(vec![
+                    CreateButton::new("embedinator_jump")
+                        .label("Jump to page")
+                        .style(ButtonStyle::Secondary),
+                ]),
+            ]
         } else {
             vec![]
         };
@@ -162,7 +179,7 @@ impl EmbedLinePaginator {
                 "embedinator_previous" => {
                     self.current_page -= 1;
                     if self.current_page == 0 {
-                        self.current_page = self.pages.len() as u8;
+                        self.current_page = self.pages.len();
                     }
                     ixn.create_response(
                         ctx.http(),
@@ -175,7 +192,7 @@ impl EmbedLinePaginator {
                 }
                 "embedinator_next" => {
                     self.current_page += 1;
-                    if self.current_page > self.pages.len() as u8 {
+                    if self.current_page > self.pages.len() {
                         self.current_page = 1;
                     }
                     ixn.create_response(
@@ -188,7 +205,7 @@ impl EmbedLinePaginator {
                     .await?;
                 }
                 "embedinator_end" => {
-                    self.current_page = self.pages.len() as u8;
+                    self.current_page = self.pages.len();
                     ixn.create_response(
                         ctx.http(),
                         CreateInteractionResponse::UpdateMessage(
@@ -198,6 +215,38 @@ impl EmbedLinePaginator {
                     )
                     .await?;
                 }
+                "embedinator_jump" => {
+                    let Some(response) = ixn
+                        .quick_modal(
+                            ctx.serenity_context(),
+                            CreateQuickModal::new("Jump to page")
+                                .short_field(format!("Page (1-{})", self.pages.len()))
+                                .timeout(Duration::from_secs(60)),
+                        )
+                        .await?
+                    else {
+                        continue;
+                    };
+
+                    // out-of-range pages are clamped rather than rejected
+                    if let Some(page) = response
+                        .inputs
+                        .first()
+                        .and_then(|input| input.trim().parse::<usize>().ok())
+                    {
+                        self.current_page = page.clamp(1, self.pages.len());
+                    }
+                    response
+                        .interaction
+                        .create_response(
+                            ctx.http(),
+                            CreateInteractionResponse::UpdateMessage(
+                                CreateInteractionResponseMessage::new()
+                                    .embed(self.embed_for(ctx, self.current_page)),
+                            ),
+                        )
+                        .await?;
+                }
                 "embedinator_stop" => break,
                 _ => {}
             }
@@ -1,16 +1,17 @@
-use std::{ops::Sub as _, sync::Arc};
+use std::ops::Sub as _;
 
-use anyhow::anyhow;
+use anyhow::{Context as _, anyhow};
 use chrono::{DateTime, Duration, Utc};
+use entity::roster_link;
 use itertools::Itertools as _;
+use sea_orm::{DatabaseConnection, EntityTrait as _};
 use serde::Deserialize;
-use tokio::sync::RwLock;
+use serenity::all::{User, UserId};
+use std::collections::HashMap;
 
 use crate::{
-    AppError, AppVars, VarsRosterSpreadsheet,
-    util::{
-        gdrive::GoogleServiceAccount, gforms::submit_google_form, gsheets::get_spreadsheet_range,
-    },
+    AppError, AppVars,
+    util::{gforms::submit_google_form, roster_source::RosterSource},
 };
 
 #[derive(Debug, Deserialize, Clone)]
@@ -19,6 +20,8 @@ pub(crate) struct RosterSheetRow {
     pub(crate) email: String,
     pub(crate) discord: String,
     pub(crate) committees: Vec<String>,
+    /// From the optional fifth roster column
+    pub(crate) discord_id: Option<UserId>,
 }
 
 impl RosterSheetRow {
@@ -27,31 +30,69 @@ impl RosterSheetRow {
     }
 }
 
-fn parse_committees_string(committees_text: &str) -> Vec<String> {
-    committees_text
-        .split(", ")
-        .map(|val| val.to_lowercase().replace('_', ""))
-        .collect_vec()
+/// The roster along with the Discord accounts members linked with `/roster link`
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RosterSnapshot {
+    pub(crate) rows: Vec<RosterSheetRow>,
+    /// Lowercased email each linked account belongs to
+    links: HashMap<UserId, String>,
+}
+
+impl RosterSnapshot {
+    /// Finds a Discord user's roster row, preferring their linked email, then the Discord user ID
+    /// column, then their username. Usernames only match rows that don't have a user ID or link,
+    /// so someone who takes a member's old username doesn't inherit their row.
+    pub(crate) fn find(&self, user_id: UserId, username: &str) -> Option<&RosterSheetRow> {
+        if let Some(email) = self.links.get(&user_id) {
+            return self
+                .rows
+                .iter()
+                .find(|row| row.email.eq_ignore_ascii_case(email));
+        }
+
+        self.rows
+            .iter()
+            .find(|row| row.discord_id == Some(user_id))
+            .or_else(|| {
+                self.rows.iter().find(|row| {
+                    row.discord_id.is_none()
+                        && row.discord.eq_ignore_ascii_case(username)
+                        && !self.is_linked(&row.email)
+                })
+            })
+    }
+
+    /// The Discord account known to belong to a roster row, if it's linked or has a user ID
+    pub(crate) fn discord_id_of(&self, row: &RosterSheetRow) -> Option<UserId> {
+        self.links
+            .iter()
+            .find(|(_, email)| row.email.eq_ignore_ascii_case(email))
+            .map(|(&user_id, _)| user_id)
+            .or(row.discord_id)
+    }
+
+    fn is_linked(&self, email: &str) -> bool {
+        self.links
+            .values()
+            .any(|linked| linked.eq_ignore_ascii_case(email))
+    }
 }
 
 pub(crate) struct Roster {
     // TODO perhaps change to HashSet and add lookup operations as struct methods instead of standalone functions
-    data: Vec<RosterSheetRow>,
+    snapshot: RosterSnapshot,
     last_updated: DateTime<Utc>,
-    service_account: Arc<RwLock<GoogleServiceAccount>>,
-    spreadsheet_vars: VarsRosterSpreadsheet,
+    source: Box<dyn RosterSource>,
+    db: DatabaseConnection,
 }
 
 impl Roster {
-    pub(crate) fn new(
-        spreadsheet_vars: &VarsRosterSpreadsheet,
-        service_account: Arc<RwLock<GoogleServiceAccount>>,
-    ) -> Roster {
+    pub(crate) fn new(source: Box<dyn RosterSource>, db: DatabaseConnection) -> Roster {
         Roster {
-            data: vec![],
+            snapshot: RosterSnapshot::default(),
             last_updated: DateTime::default(),
-            service_account,
-            spreadsheet_vars: spreadsheet_vars.clone(),
+            source,
+            db,
         }
     }
 
@@ -62,73 +103,68 @@ impl Roster {
         }
 
         println!("needs new lookup");
-        let spreadsheet = &self.spreadsheet_vars;
-        self.data = get_spreadsheet_range(
-            self.service_account.clone(),
-            &spreadsheet.id,
-            &spreadsheet.range,
-        )
-        .await?
-        .values
-        .into_iter()
-        .filter_map(|row| {
-            let [name, email, discord, committees] = row.into_iter().collect_array::<4>()?;
-            let committees = parse_committees_string(&committees);
-            Some(RosterSheetRow {
-                name,
-                email,
-                discord,
-                committees,
-            })
-        })
-        .collect_vec();
+        self.snapshot.rows = self.source.fetch_rows().await?;
+        self.reload_links().await?;
 
         self.last_updated = Utc::now();
 
         Ok(())
     }
 
+    /// Picks up changes to linked accounts without refetching the sheet
+    pub(crate) async fn reload_links(&mut self) -> Result<(), AppError> {
+        self.snapshot.links = roster_link::Entity::find()
+            .all(&self.db)
+            .await
+            .context("fetch roster links")?
+            .into_iter()
+            .map(|link| (UserId::new(link.discord_id as u64), link.email))
+            .collect();
+
+        Ok(())
+    }
+
     pub(crate) async fn fetch(
         &mut self,
         max_age_mins: i64,
     ) -> Result<&Vec<RosterSheetRow>, AppError> {
         self.refresh_if_needed(max_age_mins).await?;
 
-        Ok(&self.data)
+        Ok(&self.snapshot.rows)
+    }
+
+    /// Like [`Roster::fetch`], but keeps linked accounts so rows can be matched to Discord users
+    pub(crate) async fn snapshot(&mut self, max_age_mins: i64) -> Result<RosterSnapshot, AppError> {
+        self.refresh_if_needed(max_age_mins).await?;
+
+        Ok(self.snapshot.clone())
     }
 
     pub(crate) async fn get_user_from_discord(
         &mut self,
-        username: &str,
+        user: &User,
         force_refresh: bool,
     ) -> Result<Option<RosterSheetRow>, AppError> {
         #[expect(clippy::bool_to_int_with_if)]
         self.refresh_if_needed(if force_refresh { 0 } else { 1 })
             .await?;
 
-        let user = self
-            .data
-            .iter()
-            .find(|row| row.discord.eq_ignore_ascii_case(username))
-            .cloned();
-
-        Ok(user)
+        Ok(self.snapshot.find(user.id, &user.name).cloned())
     }
 
+    /// Roster rows for each of the users that are on the roster
     pub(crate) async fn get_users_from_discord(
         &mut self,
-        usernames: &[&str],
+        users: &[&User],
         force_refresh: bool,
-    ) -> Result<Vec<RosterSheetRow>, AppError> {
+    ) -> Result<Vec<(UserId, RosterSheetRow)>, AppError> {
         #[expect(clippy::bool_to_int_with_if)]
         self.refresh_if_needed(if force_refresh { 0 } else { 1 })
             .await?;
 
-        let users = self
-            .data
+        let users = users
             .iter()
-            .filter(|&row| usernames.contains(&row.discord.to_lowercase().as_str()))
-            .cloned()
+            .filter_map(|user| Some((user.id, self.snapshot.find(user.id, &user.name)?.clone())))
             .collect_vec();
 
         Ok(users)
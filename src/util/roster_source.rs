@@ -0,0 +1,221 @@
+// Where the roster is read from, picked with ROSTER_SOURCE__KIND
+
+use std::sync::Arc;
+
+use anyhow::{Context as _, bail};
+use entity::roster_member;
+use itertools::Itertools as _;
+use poise::BoxFuture;
+use sea_orm::{DatabaseConnection, EntityTrait as _};
+use serenity::all::UserId;
+use tokio::sync::RwLock;
+
+use crate::{
+    Vars,
+    util::{gdrive::GoogleServiceAccount, gsheets::get_spreadsheet_range, roster::RosterSheetRow},
+};
+
+pub(crate) trait RosterSource: Send + Sync {
+    /// Reads every row of the roster
+    fn fetch_rows(&self) -> BoxFuture<'_, anyhow::Result<Vec<RosterSheetRow>>>;
+}
+
+fn parse_committees_string(committees_text: &str) -> Vec<String> {
+    committees_text
+        .split(", ")
+        .map(|val| val.to_lowercase().replace('_', ""))
+        .collect_vec()
+}
+
+/// Builds a row from `[name, email, discord, committees, discord_id?]`, the layout shared by the
+/// roster sheet and CSV files
+fn parse_row(row: Vec<String>) -> Option<RosterSheetRow> {
+    let mut row = row.into_iter();
+    let [name, email, discord, committees] = row.by_ref().take(4).collect_array::<4>()?;
+    let committees = parse_committees_string(&committees);
+    let discord_id = row
+        .next()
+        .and_then(|id| id.trim().parse::<u64>().ok())
+        .filter(|&id| id != 0)
+        .map(UserId::new);
+    Some(RosterSheetRow {
+        name,
+        email,
+        discord,
+        committees,
+        discord_id,
+    })
+}
+
+/// The roster Google Sheet, read with the service account
+pub(crate) struct SheetRosterSource {
+    service_account: Arc<RwLock<GoogleServiceAccount>>,
+    sheet_id: String,
+    range: String,
+}
+
+impl RosterSource for SheetRosterSource {
+    fn fetch_rows(&self) -> BoxFuture<'_, anyhow::Result<Vec<RosterSheetRow>>> {
+        Box::pin(async move {
+            Ok(
+                get_spreadsheet_range(self.service_account.clone(), &self.sheet_id, &self.range)
+                    .await?
+                    .values
+                    .into_iter()
+                    .filter_map(parse_row)
+                    .collect_vec(),
+            )
+        })
+    }
+}
+
+/// Splits one CSV line into fields, allowing quoted fields with `""` escapes
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+/// A CSV file with the same columns as the roster sheet, after a header line
+pub(crate) struct CsvRosterSource {
+    path: String,
+}
+
+impl RosterSource for CsvRosterSource {
+    fn fetch_rows(&self) -> BoxFuture<'_, anyhow::Result<Vec<RosterSheetRow>>> {
+        Box::pin(async move {
+            let contents = tokio::fs::read_to_string(&self.path)
+                .await
+                .with_context(|| format!("read roster CSV at {}", self.path))?;
+
+            Ok(contents
+                .lines()
+                .skip(1)
+                .filter(|line| !line.trim().is_empty())
+                .map(split_csv_line)
+                .filter_map(parse_row)
+                .collect_vec())
+        })
+    }
+}
+
+/// The bot's own `roster_member` table
+pub(crate) struct DbRosterSource {
+    db: DatabaseConnection,
+}
+
+impl RosterSource for DbRosterSource {
+    fn fetch_rows(&self) -> BoxFuture<'_, anyhow::Result<Vec<RosterSheetRow>>> {
+        Box::pin(async move {
+            Ok(roster_member::Entity::find()
+                .all(&self.db)
+                .await
+                .context("fetch roster members")?
+                .into_iter()
+                .map(|member| RosterSheetRow {
+                    name: member.name,
+                    email: member.email,
+                    discord: member.discord,
+                    committees: parse_committees_string(&member.committees),
+                    discord_id: member.discord_id.map(|id| UserId::new(id as u64)),
+                })
+                .collect_vec())
+        })
+    }
+}
+
+/// Picks the roster source named by ROSTER_SOURCE__KIND, defaulting to the Google Sheet
+pub(crate) fn roster_source_from_env(
+    env: &Vars,
+    service_account: Arc<RwLock<GoogleServiceAccount>>,
+    db: DatabaseConnection,
+) -> anyhow::Result<Box<dyn RosterSource>> {
+    let source: Box<dyn RosterSource> = match env.roster_source.kind.as_str() {
+        "" | "sheet" => Box::new(SheetRosterSource {
+            service_account,
+            sheet_id: env.roster_spreadsheet.id.clone(),
+            range: env.roster_spreadsheet.range.clone(),
+        }),
+        "csv" => Box::new(CsvRosterSource {
+            path: env.roster_source.csv_path.clone(),
+        }),
+        "db" => Box::new(DbRosterSource { db }),
+        other => bail!("unknown roster source {other:?}; expected sheet, csv or db"),
+    };
+
+    Ok(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_row, split_csv_line};
+    use serenity::all::UserId;
+
+    #[test]
+    fn plain_fields() {
+        assert_eq!(
+            split_csv_line("Peter,peter@uci.edu,peter,board"),
+            ["Peter", "peter@uci.edu", "peter", "board"]
+        );
+    }
+
+    #[test]
+    fn quoted_fields_keep_commas() {
+        assert_eq!(
+            split_csv_line(r#"Peter,peter@uci.edu,peter,"board, projects""#),
+            ["Peter", "peter@uci.edu", "peter", "board, projects"]
+        );
+    }
+
+    #[test]
+    fn escaped_quotes() {
+        assert_eq!(
+            split_csv_line(r#""Peter ""The Anteater""",peter@uci.edu"#),
+            [r#"Peter "The Anteater""#, "peter@uci.edu"]
+        );
+    }
+
+    #[test]
+    fn empty_fields() {
+        assert_eq!(split_csv_line("Peter,,,"), ["Peter", "", "", ""]);
+    }
+
+    #[test]
+    fn row_with_discord_id() {
+        let row = parse_row(split_csv_line(
+            r#"Peter,peter@uci.edu,peter,"Board, Web_Dev",1234"#,
+        ))
+        .unwrap();
+        assert_eq!(row.name, "Peter");
+        assert_eq!(row.committees, ["board", "webdev"]);
+        assert_eq!(row.discord_id, Some(UserId::new(1234)));
+    }
+
+    #[test]
+    fn row_without_discord_id() {
+        let row = parse_row(split_csv_line("Peter,peter@uci.edu,peter,board,")).unwrap();
+        assert_eq!(row.discord_id, None);
+    }
+
+    #[test]
+    fn short_rows_are_skipped() {
+        assert!(parse_row(split_csv_line("Peter,peter@uci.edu,peter")).is_none());
+        assert!(parse_row(split_csv_line("")).is_none());
+    }
+}
@@ -0,0 +1,39 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone as _, Utc};
+use chrono_tz::Tz;
+
+/// Converts a wall clock time in `tz` to naive UTC. Times skipped by a DST transition are moved
+/// forward an hour, and ambiguous times resolve to the earlier instant.
+pub(crate) fn local_to_utc(tz: Tz, local: NaiveDateTime) -> NaiveDateTime {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + TimeDelta::hours(1)))
+                .earliest()
+        })
+        .map_or(local, |time| time.naive_utc())
+}
+
+/// Naive UTC time at which `date` starts in `tz`
+pub(crate) fn start_of_day(tz: Tz, date: NaiveDate) -> NaiveDateTime {
+    local_to_utc(tz, date.and_time(NaiveTime::MIN))
+}
+
+/// The current date in `tz`
+pub(crate) fn today(tz: Tz) -> NaiveDate {
+    Utc::now().with_timezone(&tz).date_naive()
+}
+
+/// Attaches `tz` to a naive UTC time read from the database
+pub(crate) fn from_utc(tz: Tz, utc: NaiveDateTime) -> DateTime<Tz> {
+    utc.and_utc().with_timezone(&tz)
+}
+
+/// Naive UTC time of the next `time` on the wall clock in `tz`, strictly after now
+pub(crate) fn next_local_time(tz: Tz, time: NaiveTime) -> NaiveDateTime {
+    let now = Utc::now().naive_utc();
+    let today = local_to_utc(tz, self::today(tz).and_time(time));
+    match today > now {
+        true => today,
+        false => local_to_utc(tz, (self::today(tz) + TimeDelta::days(1)).and_time(time)),
+    }
+}